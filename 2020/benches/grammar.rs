@@ -0,0 +1,49 @@
+//! Compare matching day 19 messages through a compiled regex and through
+//! the Earley parser, using the puzzle's non-recursive part 1 rules.
+//!
+//! Run with `cargo bench --bench grammar`.
+
+use advent_of_code_2020::bench::{time, Config, Timing};
+use advent_of_code_2020::grammar::{Grammar, Matcher};
+use advent_of_code_2020::input::Inputs;
+
+fn report(name: &str, timing: Timing) {
+  println!(
+    "{:<8} min {:>10.3?}  mean {:>10.3?}  max {:>10.3?}",
+    name, timing.min, timing.mean, timing.max
+  );
+}
+
+fn main() {
+  let mut inputs = Inputs::from_env();
+  let input = inputs.get(19).unwrap_or_else(|err| panic!("{}", err));
+  let (rules, messages) = input
+    .trim()
+    .split_once("\n\n")
+    .expect("rules and messages are separated by a blank line");
+  let grammar = Grammar::parse(rules).unwrap_or_else(|err| panic!("{}", err));
+  let messages: Vec<&str> = messages.lines().collect();
+
+  let regex = grammar.matcher(0);
+  assert!(
+    matches!(regex, Matcher::Regex(_)),
+    "rule 0 should compile to a regex"
+  );
+  let earley = Matcher::Earley {
+    grammar: &grammar,
+    start: 0,
+  };
+  let count = |matcher: &Matcher| {
+    messages
+      .iter()
+      .filter(|message| matcher.matches(message))
+      .count()
+  };
+  assert_eq!(count(&regex), count(&earley));
+  println!("{} of {} messages match", count(&regex), messages.len());
+
+  let config = Config::default();
+  report("compile", time(config, || grammar.to_regex(0)));
+  report("regex", time(config, || count(&regex)));
+  report("earley", time(config, || count(&earley)));
+}
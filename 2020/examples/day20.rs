@@ -0,0 +1,50 @@
+//! Show what day 20 assembled: the tile layout, or the stitched image with
+//! sea monsters marked as text, a PBM bitmap or a PPM pixmap.
+//!
+//! Run with `cargo run --example day20 -- [layout|text|pbm|ppm] > out`.
+//! The input is found the same way as for the `aoc` binary.
+
+use std::env;
+use std::process;
+
+use advent_of_code_2020::day::day20::{
+  assemble, find_oriented, highlight, parse, to_pbm, to_ppm, AssemblyError, Pattern, SEA_MONSTER,
+};
+use advent_of_code_2020::input::Inputs;
+
+fn main() {
+  let format = env::args().nth(1).unwrap_or_else(|| "layout".into());
+  let mut inputs = Inputs::from_env();
+  let input = inputs.get(20).unwrap_or_else(|err| panic!("{}", err));
+  let tiles = parse(input).unwrap_or_else(|err| panic!("{}", err));
+  let assembly = match assemble(&tiles) {
+    Ok(assembly) => assembly,
+    Err(AssemblyError::Ambiguous(first, second)) => {
+      eprintln!("the tiles fit together in more than one way, including");
+      eprint!("{}\nand\n{}", first.layout(), second.layout());
+      process::exit(1);
+    }
+    Err(err) => {
+      eprintln!("{}", err);
+      process::exit(1);
+    }
+  };
+  let image = assembly.image();
+  let monster = Pattern::parse(SEA_MONSTER).unwrap();
+  let matches = find_oriented(&image, &monster);
+
+  let out = match format.as_str() {
+    "layout" => assembly.layout(),
+    "text" => highlight(&image, &matches),
+    "pbm" => to_pbm(&image),
+    "ppm" => to_ppm(&image, &matches),
+    other => {
+      eprintln!(
+        "unknown format {:?}, expected layout, text, pbm or ppm",
+        other
+      );
+      process::exit(2);
+    }
+  };
+  print!("{}", out);
+}
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+use crate::input;
+use crate::{Error, Result};
+
+/// Environment variable naming the answers file.
+pub const ANSWERS_FILE_VAR: &str = "AOC_ANSWERS";
+
+/// Name of the answers file looked for next to the inputs.
+pub const ANSWERS_FILE_NAME: &str = "answers.toml";
+
+/// The year these solutions are for, and the table answers are read from.
+pub const YEAR: u32 = 2020;
+
+/// Accepted answers keyed by year, day and part, read from a TOML file like
+///
+/// ```toml
+/// [2020.day01]
+/// part1 = 928896
+/// part2 = "295668576"
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use advent_of_code_2020::answers::{Answers, Verdict};
+///
+/// let answers = Answers::parse("[2020.day01]\npart1 = 514579\n").unwrap();
+/// assert_eq!(answers.get(2020, 1, 1), Some("514579"));
+/// assert_eq!(answers.check(1, 1, "514579"), Verdict::Pass);
+/// assert_eq!(
+///   answers.check(1, 1, "42"),
+///   Verdict::Fail {
+///     expected: "514579".into()
+///   }
+/// );
+/// assert_eq!(answers.check(1, 2, "241861950"), Verdict::Unknown);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Answers {
+  answers: HashMap<(u32, u32, u32), String>,
+}
+
+/// How an answer compares to the accepted one
+#[derive(Clone, Debug, PartialEq)]
+pub enum Verdict {
+  Pass,
+  Fail { expected: String },
+  Unknown,
+}
+
+impl fmt::Display for Verdict {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Verdict::Pass => write!(f, "pass"),
+      Verdict::Fail { expected } => write!(f, "FAIL (expected {})", expected),
+      Verdict::Unknown => write!(f, "unknown"),
+    }
+  }
+}
+
+impl Answers {
+  /// Read answers from the TOML `i`. Tables are named `<year>` with a
+  /// `day<NN>` table per day holding `part1` and `part2` values.
+  pub fn parse(i: &str) -> Result<Self> {
+    let value: toml::Value = i.parse().map_err(|e: toml::de::Error| {
+      let (line, column) = e.line_col().unwrap_or((0, 0));
+      let text = i.lines().nth(line).unwrap_or_default();
+      // The position is reported separately, so drop it from toml's message
+      let message = e.to_string();
+      let message = message.split(" at line ").next().unwrap_or_default();
+      Error::parse(line + 1, column + 1, text, message)
+    })?;
+    let mut answers = HashMap::new();
+    for (year, days) in table(i, "the top level", &value)? {
+      let year: u32 = year
+        .parse()
+        .map_err(|_| key_error(i, year, "expected a year"))?;
+      for (day, parts) in table(i, year.to_string().as_str(), days)? {
+        let day_number: u32 = day
+          .strip_prefix("day")
+          .and_then(|day| day.parse().ok())
+          .ok_or_else(|| key_error(i, day, "expected `day<NN>`"))?;
+        for (part, answer) in table(i, day, parts)? {
+          let part_number = match part.as_str() {
+            "part1" => 1,
+            "part2" => 2,
+            _ => return Err(key_error(i, part, "expected `part1` or `part2`")),
+          };
+          let answer = match answer {
+            toml::Value::String(s) => s.clone(),
+            toml::Value::Integer(n) => n.to_string(),
+            _ => return Err(key_error(i, part, "expected a string or integer answer")),
+          };
+          answers.insert((year, day_number, part_number), answer);
+        }
+      }
+    }
+    Ok(Self { answers })
+  }
+
+  /// Read answers from the file at `path`
+  pub fn load(path: &Path) -> Result<Self> {
+    Self::parse(&input::read_file(path)?)
+  }
+
+  pub fn get(&self, year: u32, day: u32, part: u32) -> Option<&str> {
+    self.answers.get(&(year, day, part)).map(|s| s.as_str())
+  }
+
+  /// Compare an answer for this year's `day` and `part` against the accepted one
+  pub fn check(&self, day: u32, part: u32, answer: &str) -> Verdict {
+    match self.get(YEAR, day, part) {
+      Some(expected) if expected == answer => Verdict::Pass,
+      Some(expected) => Verdict::Fail {
+        expected: expected.into(),
+      },
+      None => Verdict::Unknown,
+    }
+  }
+}
+
+fn table<'a>(i: &str, name: &str, value: &'a toml::Value) -> Result<&'a toml::value::Table> {
+  value
+    .as_table()
+    .ok_or_else(|| key_error(i, name, "expected a table"))
+}
+
+/// TOML values don't keep their position, so point at the first line
+/// mentioning `key` instead.
+fn key_error(i: &str, key: &str, message: &str) -> Error {
+  match i.lines().find(|line| line.contains(key)) {
+    Some(line) => Error::at(i, line, format!("{} for `{}`", message, key)),
+    None => Error::parse(1, 1, key, message),
+  }
+}
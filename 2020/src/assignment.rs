@@ -0,0 +1,218 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+/// Why no single assignment could be found
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AssignmentError {
+  /// However the choices are made, these items are left without one
+  NoAssignment(Vec<usize>),
+  /// More than one assignment works. These are two of them.
+  Ambiguous(Vec<usize>, Vec<usize>),
+}
+
+impl fmt::Display for AssignmentError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      AssignmentError::NoAssignment(unmatched) => write!(
+        f,
+        "no assignment gives every item a choice, {} can't be matched",
+        unmatched.len()
+      ),
+      AssignmentError::Ambiguous(..) => write!(f, "more than one assignment is possible"),
+    }
+  }
+}
+
+impl std::error::Error for AssignmentError {}
+
+/// Give each item a different one of its `candidates`, returning the choice
+/// for each item. Choices are numbered from 0 and may outnumber the items,
+/// in which case some are left over. It's an error unless there is exactly
+/// one way to do it.
+///
+/// Finds a maximum matching with Hopcroft–Karp, then looks for another one
+/// that also matches every item: either an item could move to a left over
+/// choice, or a ring of items could each take the next one's choice.
+///
+/// # Example
+///
+/// ```
+/// use advent_of_code_2020::assignment::{assign, AssignmentError};
+///
+/// assert_eq!(assign(&[vec![0, 1], vec![0], vec![1, 2]]), Ok(vec![1, 0, 2]));
+/// assert_eq!(
+///   assign(&[vec![0], vec![0], vec![1]]),
+///   Err(AssignmentError::NoAssignment(vec![1]))
+/// );
+/// assert_eq!(
+///   assign(&[vec![0, 1], vec![0, 1]]),
+///   Err(AssignmentError::Ambiguous(vec![0, 1], vec![1, 0]))
+/// );
+/// assert!(assign(&[vec![0], vec![1, 2]]).is_err());
+/// ```
+pub fn assign(candidates: &[Vec<usize>]) -> Result<Vec<usize>, AssignmentError> {
+  let choices = candidates.iter().flatten().max().map_or(0, |&max| max + 1);
+  let matching = Matching::new(candidates, choices).maximum();
+
+  let unmatched: Vec<usize> = (0..candidates.len())
+    .filter(|&item| matching.choice[item].is_none())
+    .collect();
+  if !unmatched.is_empty() {
+    return Err(AssignmentError::NoAssignment(unmatched));
+  }
+  let assignment: Vec<usize> = matching.choice.iter().map(|c| c.unwrap()).collect();
+
+  // An item that could take a left over choice instead
+  for (item, options) in candidates.iter().enumerate() {
+    if let Some(&free) = options.iter().find(|&&c| matching.item[c].is_none()) {
+      let mut other = assignment.clone();
+      other[item] = free;
+      return Err(AssignmentError::Ambiguous(assignment, other));
+    }
+  }
+
+  // A ring of items, each of which could take the next one's choice
+  if let Some(ring) = find_ring(candidates, &assignment, &matching.item) {
+    let mut other = assignment.clone();
+    for (idx, &item) in ring.iter().enumerate() {
+      other[item] = assignment[ring[(idx + 1) % ring.len()]];
+    }
+    return Err(AssignmentError::Ambiguous(assignment, other));
+  }
+  Ok(assignment)
+}
+
+/// A matching between items and choices, grown by Hopcroft–Karp
+struct Matching<'a> {
+  candidates: &'a [Vec<usize>],
+  /// The choice matched to each item
+  choice: Vec<Option<usize>>,
+  /// The item matched to each choice
+  item: Vec<Option<usize>>,
+  /// Each item's distance from an unmatched item in the current phase
+  layer: Vec<Option<usize>>,
+}
+
+impl<'a> Matching<'a> {
+  fn new(candidates: &'a [Vec<usize>], choices: usize) -> Self {
+    Self {
+      candidates,
+      choice: vec![None; candidates.len()],
+      item: vec![None; choices],
+      layer: vec![None; candidates.len()],
+    }
+  }
+
+  fn maximum(mut self) -> Self {
+    while self.layers() {
+      for item in 0..self.candidates.len() {
+        if self.choice[item].is_none() {
+          self.augment(item);
+        }
+      }
+    }
+    self
+  }
+
+  /// Layer the items by breadth first search from the unmatched ones,
+  /// returning whether any augmenting path exists
+  fn layers(&mut self) -> bool {
+    let mut queue = VecDeque::new();
+    for item in 0..self.candidates.len() {
+      self.layer[item] = match self.choice[item] {
+        None => {
+          queue.push_back(item);
+          Some(0)
+        }
+        Some(_) => None,
+      };
+    }
+    let mut found = false;
+    while let Some(item) = queue.pop_front() {
+      let next = self.layer[item].map(|layer| layer + 1);
+      for &choice in &self.candidates[item] {
+        match self.item[choice] {
+          None => found = true,
+          Some(other) if self.layer[other].is_none() => {
+            self.layer[other] = next;
+            queue.push_back(other);
+          }
+          Some(_) => {}
+        }
+      }
+    }
+    found
+  }
+
+  /// Follow the layers from `item` to an unmatched choice, flipping the
+  /// path into the matching if one is found
+  fn augment(&mut self, item: usize) -> bool {
+    let next = self.layer[item].map(|layer| layer + 1);
+    for idx in 0..self.candidates[item].len() {
+      let choice = self.candidates[item][idx];
+      let reached = match self.item[choice] {
+        None => true,
+        Some(other) => self.layer[other] == next && self.augment(other),
+      };
+      if reached {
+        self.choice[item] = Some(choice);
+        self.item[choice] = Some(item);
+        return true;
+      }
+    }
+    // Nothing useful beyond here for the rest of this phase
+    self.layer[item] = None;
+    false
+  }
+}
+
+/// Items `a`, `b`, ... where `a` could take `b`'s choice, `b` the next
+/// one's and so on back round to `a`
+fn find_ring(
+  candidates: &[Vec<usize>],
+  assignment: &[usize],
+  item_of: &[Option<usize>],
+) -> Option<Vec<usize>> {
+  #[derive(Clone, Copy, PartialEq)]
+  enum State {
+    New,
+    OnPath,
+    Done,
+  }
+  let mut state = vec![State::New; candidates.len()];
+  for start in 0..candidates.len() {
+    if state[start] != State::New {
+      continue;
+    }
+    // Depth first, keeping each item on the path with how far through its
+    // candidates it has got
+    let mut path = vec![(start, 0)];
+    state[start] = State::OnPath;
+    while let Some(&mut (item, ref mut next)) = path.last_mut() {
+      let options = &candidates[item];
+      if *next == options.len() {
+        state[item] = State::Done;
+        path.pop();
+        continue;
+      }
+      let choice = options[*next];
+      *next += 1;
+      if choice == assignment[item] {
+        continue;
+      }
+      let other = item_of[choice].expect("every candidate choice is taken");
+      match state[other] {
+        State::New => {
+          state[other] = State::OnPath;
+          path.push((other, 0));
+        }
+        State::OnPath => {
+          let from = path.iter().position(|&(i, _)| i == other).unwrap();
+          return Some(path[from..].iter().map(|&(i, _)| i).collect());
+        }
+        State::Done => {}
+      }
+    }
+  }
+  None
+}
@@ -0,0 +1,171 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::hex::HexDirection;
+
+/// A cell in `D` dimensional space
+pub type Point<const D: usize> = [i64; D];
+
+/// The offsets from a cell to each of its neighbours
+#[derive(Clone, Debug, PartialEq)]
+pub struct Neighbourhood<const D: usize> {
+  offsets: Vec<Point<D>>,
+}
+
+impl<const D: usize> Neighbourhood<D> {
+  pub fn from_offsets(offsets: Vec<Point<D>>) -> Self {
+    Self { offsets }
+  }
+
+  /// Every cell within one step along any combination of axes, 3^D - 1 neighbours
+  pub fn moore() -> Self {
+    let offsets = (0..3usize.pow(D as u32))
+      .map(|n| {
+        let mut offset = [0; D];
+        let mut n = n;
+        for axis in offset.iter_mut() {
+          *axis = (n % 3) as i64 - 1;
+          n /= 3;
+        }
+        offset
+      })
+      .filter(|offset| offset.iter().any(|&d| d != 0))
+      .collect();
+    Self { offsets }
+  }
+
+  /// Cells one step along a single axis, 2 * D neighbours
+  pub fn von_neumann() -> Self {
+    let offsets = (0..D)
+      .flat_map(|axis| {
+        [-1, 1].iter().map(move |&d| {
+          let mut offset = [0; D];
+          offset[axis] = d;
+          offset
+        })
+      })
+      .collect();
+    Self { offsets }
+  }
+
+  pub fn offsets(&self) -> &[Point<D>] {
+    &self.offsets
+  }
+}
+
+impl Neighbourhood<2> {
+  /// The six neighbours of a hexagon in the axial `[q, r]` coordinates of `Hex`
+  pub fn hex() -> Self {
+    Self::from_offsets(
+      HexDirection::ALL
+        .iter()
+        .map(|direction| direction.offset().into())
+        .collect(),
+    )
+  }
+}
+
+/// How many active neighbours bring a cell to life or keep it alive
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rule {
+  birth: Vec<usize>,
+  survival: Vec<usize>,
+}
+
+impl Rule {
+  pub fn new(birth: &[usize], survival: &[usize]) -> Self {
+    Self {
+      birth: birth.to_vec(),
+      survival: survival.to_vec(),
+    }
+  }
+
+  fn is_active(&self, active: bool, neighbours: usize) -> bool {
+    if active {
+      self.survival.contains(&neighbours)
+    } else {
+      self.birth.contains(&neighbours)
+    }
+  }
+}
+
+/// A cellular automaton storing only its active cells, so it can grow
+/// without bounds in every direction.
+///
+/// # Example
+///
+/// ```
+/// use advent_of_code_2020::automaton::{Automaton, Neighbourhood, Rule};
+///
+/// // A blinker in Conway's Game of Life flips between a row and a column
+/// let row = vec![[0, 1], [1, 1], [2, 1]];
+/// let mut life = Automaton::new(row.clone(), Neighbourhood::moore(), Rule::new(&[3], &[2, 3]));
+/// life.step();
+/// let mut active: Vec<_> = life.active().iter().copied().collect();
+/// active.sort();
+/// assert_eq!(active, vec![[1, 0], [1, 1], [1, 2]]);
+/// life.step();
+/// assert_eq!(life.active(), &row.into_iter().collect());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Automaton<const D: usize> {
+  active: HashSet<Point<D>>,
+  neighbourhood: Neighbourhood<D>,
+  rule: Rule,
+}
+
+impl<const D: usize> Automaton<D> {
+  pub fn new(
+    active: impl IntoIterator<Item = Point<D>>,
+    neighbourhood: Neighbourhood<D>,
+    rule: Rule,
+  ) -> Self {
+    Self {
+      active: active.into_iter().collect(),
+      neighbourhood,
+      rule,
+    }
+  }
+
+  pub fn active(&self) -> &HashSet<Point<D>> {
+    &self.active
+  }
+
+  pub fn count(&self) -> usize {
+    self.active.len()
+  }
+
+  /// Apply the rule to every cell at once
+  pub fn step(&mut self) {
+    let mut counts: HashMap<Point<D>, usize> = HashMap::new();
+    for cell in &self.active {
+      for offset in self.neighbourhood.offsets() {
+        let mut neighbour = *cell;
+        neighbour
+          .iter_mut()
+          .zip(offset)
+          .for_each(|(axis, d)| *axis += d);
+        *counts.entry(neighbour).or_default() += 1;
+      }
+    }
+    // Active cells with no active neighbours never show up in `counts`
+    let lonely = self
+      .active
+      .iter()
+      .filter(|cell| !counts.contains_key(*cell))
+      .map(|cell| (*cell, 0));
+    let next = counts
+      .iter()
+      .map(|(cell, count)| (*cell, *count))
+      .chain(lonely)
+      .filter(|(cell, count)| self.rule.is_active(self.active.contains(cell), *count))
+      .map(|(cell, _)| cell)
+      .collect();
+    self.active = next;
+  }
+
+  pub fn run(&mut self, steps: usize) {
+    for _ in 0..steps {
+      self.step();
+    }
+  }
+}
@@ -0,0 +1,182 @@
+use std::fmt::Write;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use crate::{DynSolution, Result};
+
+/// How many untimed warm-up runs precede the timed iterations
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Config {
+  pub warmup: u32,
+  pub iterations: u32,
+}
+
+impl Default for Config {
+  fn default() -> Self {
+    Self {
+      warmup: 1,
+      iterations: 5,
+    }
+  }
+}
+
+/// Fastest, mean and slowest of the timed iterations
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Timing {
+  pub min: Duration,
+  pub mean: Duration,
+  pub max: Duration,
+}
+
+/// Time `f`, discarding the warm-up runs. At least one iteration is always timed.
+///
+/// # Example
+///
+/// ```
+/// use advent_of_code_2020::bench::{time, Config};
+///
+/// let config = Config {
+///   warmup: 1,
+///   iterations: 3,
+/// };
+/// let timing = time(config, || (1..1000u64).sum::<u64>());
+/// assert!(timing.min <= timing.mean && timing.mean <= timing.max);
+/// ```
+pub fn time<T>(config: Config, mut f: impl FnMut() -> T) -> Timing {
+  for _ in 0..config.warmup {
+    black_box(f());
+  }
+  let iterations = config.iterations.max(1);
+  let durations: Vec<Duration> = (0..iterations)
+    .map(|_| {
+      let start = Instant::now();
+      black_box(f());
+      start.elapsed()
+    })
+    .collect();
+  Timing {
+    min: durations.iter().copied().min().unwrap_or_default(),
+    mean: durations.iter().sum::<Duration>() / iterations,
+    max: durations.iter().copied().max().unwrap_or_default(),
+  }
+}
+
+/// Timings for one day. Parts are only present when they were benchmarked.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DayTimings {
+  pub day: u32,
+  pub parse: Timing,
+  pub part1: Option<Timing>,
+  pub part2: Option<Timing>,
+}
+
+impl DayTimings {
+  fn stages(&self) -> impl Iterator<Item = (&'static str, Timing)> {
+    vec![
+      Some(("parse", self.parse)),
+      self.part1.map(|t| ("part1", t)),
+      self.part2.map(|t| ("part2", t)),
+    ]
+    .into_iter()
+    .flatten()
+  }
+}
+
+/// Time parsing `input` and solving each of `parts` separately, each part
+/// reusing a single parsed input.
+pub fn bench_day(
+  day: u32,
+  solution: &dyn DynSolution,
+  input: &str,
+  parts: &[u32],
+  config: Config,
+) -> Result<DayTimings> {
+  let parsed = solution.parse_dyn(input)?;
+  let parse = time(config, || solution.parse_dyn(input));
+  let part = |part| {
+    parts.contains(&part).then(|| match part {
+      1 => time(config, || solution.part1_dyn(parsed.as_ref())),
+      _ => time(config, || solution.part2_dyn(parsed.as_ref())),
+    })
+  };
+  Ok(DayTimings {
+    day,
+    parse,
+    part1: part(1),
+    part2: part(2),
+  })
+}
+
+/// Render timings as a table with a row per day and stage
+pub fn table(timings: &[DayTimings]) -> String {
+  let mut out = format!(
+    "{:<5}{:<8}{:>12}{:>12}{:>12}\n",
+    "Day", "Stage", "Min", "Mean", "Max"
+  );
+  for day in timings {
+    for (stage, t) in day.stages() {
+      let _ = writeln!(
+        out,
+        "{:<5}{:<8}{:>12}{:>12}{:>12}",
+        format!("{:02}", day.day),
+        stage,
+        format!("{:.2?}", t.min),
+        format!("{:.2?}", t.mean),
+        format!("{:.2?}", t.max)
+      );
+    }
+  }
+  out
+}
+
+/// Render timings as JSON, with durations in nanoseconds, so runs can be
+/// saved and compared.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use advent_of_code_2020::bench::{json, Config, DayTimings, Timing};
+///
+/// let t = Timing {
+///   min: Duration::from_nanos(1),
+///   mean: Duration::from_nanos(2),
+///   max: Duration::from_nanos(3),
+/// };
+/// let timings = vec![DayTimings {
+///   day: 1,
+///   parse: t,
+///   part1: Some(t),
+///   part2: None,
+/// }];
+/// assert_eq!(
+///   json(Config::default(), &timings),
+///   r#"{"warmup":1,"iterations":5,"days":[{"day":1,"parse":{"min_ns":1,"mean_ns":2,"max_ns":3},"part1":{"min_ns":1,"mean_ns":2,"max_ns":3}}]}"#
+/// );
+/// ```
+pub fn json(config: Config, timings: &[DayTimings]) -> String {
+  let days: Vec<String> = timings
+    .iter()
+    .map(|day| {
+      let stages: Vec<String> = day
+        .stages()
+        .map(|(stage, t)| {
+          format!(
+            r#""{}":{{"min_ns":{},"mean_ns":{},"max_ns":{}}}"#,
+            stage,
+            t.min.as_nanos(),
+            t.mean.as_nanos(),
+            t.max.as_nanos()
+          )
+        })
+        .collect();
+      format!(r#"{{"day":{},{}}}"#, day.day, stages.join(","))
+    })
+    .collect();
+  format!(
+    r#"{{"warmup":{},"iterations":{},"days":[{}]}}"#,
+    config.warmup,
+    config.iterations,
+    days.join(",")
+  )
+}
@@ -0,0 +1,321 @@
+use std::env;
+use std::path::Path;
+use std::process;
+
+use advent_of_code_2020::answers::{self, Answers, Verdict};
+use advent_of_code_2020::bench::{self, DayTimings};
+use advent_of_code_2020::day::{self, day07};
+use advent_of_code_2020::input::{self, Inputs};
+
+const USAGE: &str = "Usage: aoc [run|verify|bench] [--day <1-24>] [--part <1|2>] [--input <path|->]
+           [--input-dir <dir>] [--answers <path>] [--warmup <n>] [--iterations <n>] [--json]
+           [--bag <colour>] [--query <containers|count|tree>]
+
+Runs every solved day when --day is omitted. Inputs are read from
+<dir>/dayNN.txt, where <dir> is --input-dir, then $AOC_INPUT_DIR, then the
+inputs checked in to this crate. --input - reads the input from stdin.
+
+verify checks each answer against the answers file: --answers, then
+$AOC_ANSWERS, then answers.toml in the input directory.
+
+bench times parsing and each part separately, after --warmup untimed runs
+(default 1) over --iterations timed runs (default 5). --json prints the
+results as JSON instead of a table.
+
+--bag answers questions about a colour from the day 7 bag rules instead of
+running the puzzle: which bags can eventually hold it (containers), how many
+bags it holds (count) and everything inside it (tree). --query picks one;
+all three are shown without it.";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum Mode {
+  #[default]
+  Run,
+  Verify,
+  Bench,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Query {
+  Containers,
+  Count,
+  Tree,
+}
+
+#[derive(Debug, Default)]
+struct Args {
+  mode: Mode,
+  day: Option<u32>,
+  part: Option<u32>,
+  input: Option<String>,
+  input_dir: Option<String>,
+  answers: Option<String>,
+  bench: bench::Config,
+  json: bool,
+  bag: Option<String>,
+  query: Option<Query>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+  let mut parsed = Args::default();
+  while let Some(arg) = args.next() {
+    let mut value = |name: &str| {
+      args
+        .next()
+        .ok_or_else(|| format!("missing value for {}", name))
+    };
+    match arg.as_str() {
+      "run" => parsed.mode = Mode::Run,
+      "verify" => parsed.mode = Mode::Verify,
+      "bench" => parsed.mode = Mode::Bench,
+      "-d" | "--day" => parsed.day = Some(parse_number("--day", &value("--day")?, day::DAYS)?),
+      "-p" | "--part" => parsed.part = Some(parse_number("--part", &value("--part")?, 2)?),
+      "-i" | "--input" => parsed.input = Some(value("--input")?),
+      "--input-dir" => parsed.input_dir = Some(value("--input-dir")?),
+      "--answers" => parsed.answers = Some(value("--answers")?),
+      "--warmup" => parsed.bench.warmup = parse_count("--warmup", &value("--warmup")?)?,
+      "--iterations" => {
+        parsed.bench.iterations = parse_count("--iterations", &value("--iterations")?)?.max(1)
+      }
+      "--json" => parsed.json = true,
+      "--bag" => parsed.bag = Some(value("--bag")?),
+      "--query" => parsed.query = Some(parse_query(&value("--query")?)?),
+      "-h" | "--help" => {
+        println!("{}", USAGE);
+        process::exit(0);
+      }
+      other => return Err(format!("unexpected argument: {}", other)),
+    }
+  }
+  if parsed.bag.is_some() {
+    if parsed.mode != Mode::Run {
+      return Err("--bag only works with run".into());
+    }
+    match parsed.day {
+      None => parsed.day = Some(7),
+      Some(7) => {}
+      Some(_) => return Err("--bag only applies to day 7".into()),
+    }
+  } else if parsed.query.is_some() {
+    return Err("--query requires --bag".into());
+  }
+  if parsed.input.is_some() && parsed.day.is_none() {
+    return Err("--input requires --day".into());
+  }
+  Ok(parsed)
+}
+
+fn parse_query(value: &str) -> Result<Query, String> {
+  match value {
+    "containers" => Ok(Query::Containers),
+    "count" => Ok(Query::Count),
+    "tree" => Ok(Query::Tree),
+    _ => Err(format!(
+      "--query must be containers, count or tree, got {}",
+      value
+    )),
+  }
+}
+
+fn parse_number(name: &str, value: &str, max: u32) -> Result<u32, String> {
+  match value.parse() {
+    Ok(n) if n >= 1 && n <= max => Ok(n),
+    _ => Err(format!(
+      "{} must be between 1 and {}, got {}",
+      name, max, value
+    )),
+  }
+}
+
+fn parse_count(name: &str, value: &str) -> Result<u32, String> {
+  value
+    .parse()
+    .map_err(|_| format!("{} must be a number, got {}", name, value))
+}
+
+/// Find inputs in the chosen directory, with `--input` standing in for the one day
+fn load_inputs(args: &Args) -> Result<Inputs, String> {
+  let mut inputs = match &args.input_dir {
+    Some(dir) => Inputs::new(dir),
+    None => Inputs::from_env(),
+  };
+  if let (Some(day), Some(path)) = (args.day, &args.input) {
+    let contents = match path.as_str() {
+      "-" => input::read_stdin(),
+      path => input::read_file(Path::new(path)),
+    };
+    inputs.insert(day, contents.map_err(|e| e.to_string())?);
+  }
+  Ok(inputs)
+}
+
+fn load_answers(args: &Args, inputs: &Inputs) -> Result<Answers, String> {
+  let path = match (&args.answers, env::var_os(answers::ANSWERS_FILE_VAR)) {
+    (Some(path), _) => path.into(),
+    (None, Some(path)) => path.into(),
+    (None, None) => inputs.dir().join(answers::ANSWERS_FILE_NAME),
+  };
+  Answers::load(&path).map_err(|e| e.to_string())
+}
+
+/// Parse the day's input and solve each of `parts`
+fn solve(day: u32, parts: &[u32], inputs: &mut Inputs) -> Result<Vec<(u32, String)>, String> {
+  let solution = day::get(day).ok_or("no solution for this day")?;
+  let contents = inputs.get(day).map_err(|e| e.to_string())?;
+  let parsed = solution.parse_dyn(contents).map_err(|e| e.to_string())?;
+  Ok(
+    parts
+      .iter()
+      .map(|&part| match part {
+        1 => (part, solution.part1_dyn(parsed.as_ref())),
+        _ => (part, solution.part2_dyn(parsed.as_ref())),
+      })
+      .collect(),
+  )
+}
+
+fn bench_day(
+  day: u32,
+  args: &Args,
+  parts: &[u32],
+  inputs: &mut Inputs,
+) -> Result<DayTimings, String> {
+  let solution = day::get(day).ok_or("no solution for this day")?;
+  let contents = inputs.get(day).map_err(|e| e.to_string())?;
+  bench::bench_day(day, solution, contents, parts, args.bench).map_err(|e| e.to_string())
+}
+
+/// Print each answer, returning whether every day could be solved
+fn run(days: &[u32], parts: &[u32], inputs: &mut Inputs) -> bool {
+  let mut ok = true;
+  for &day in days {
+    match solve(day, parts, inputs) {
+      Ok(solved) => {
+        for (part, answer) in solved {
+          println!("Day {:02} Part {}: {}", day, part, answer);
+        }
+      }
+      Err(e) => {
+        eprintln!("Day {:02}: {}", day, e);
+        ok = false;
+      }
+    }
+  }
+  ok
+}
+
+/// Answer `queries` about the `color` bag from the day 7 rules
+fn query_bags(color: &str, queries: &[Query], inputs: &mut Inputs) -> Result<(), String> {
+  let contents = inputs.get(7).map_err(|e| e.to_string())?;
+  let bags = day07::parse(contents).map_err(|e| e.to_string())?;
+  let missing = || format!("no rule for {} bags", color);
+  for query in queries {
+    match query {
+      Query::Containers => {
+        let containers = day07::containers_of(&bags, color).ok_or_else(missing)?;
+        println!("{} colours can hold {} bags:", containers.len(), color);
+        for container in containers {
+          println!("  {}", container);
+        }
+      }
+      Query::Count => {
+        let count = day07::bags_inside(&bags, color).ok_or_else(missing)?;
+        println!("{} bags hold {} bags", color, count);
+      }
+      Query::Tree => print!(
+        "{}",
+        day07::contents_tree(&bags, color).ok_or_else(missing)?
+      ),
+    }
+  }
+  Ok(())
+}
+
+/// Check each answer against `answers`, returning whether none failed
+fn verify(days: &[u32], parts: &[u32], inputs: &mut Inputs, answers: &Answers) -> bool {
+  let (mut passed, mut failed, mut unknown) = (0, 0, 0);
+  for &day in days {
+    match solve(day, parts, inputs) {
+      Ok(solved) => {
+        for (part, answer) in solved {
+          let verdict = answers.check(day, part, &answer);
+          match verdict {
+            Verdict::Pass => passed += 1,
+            Verdict::Fail { .. } => failed += 1,
+            Verdict::Unknown => unknown += 1,
+          }
+          println!("Day {:02} Part {}: {} {}", day, part, answer, verdict);
+        }
+      }
+      Err(e) => {
+        eprintln!("Day {:02}: {}", day, e);
+        failed += 1;
+      }
+    }
+  }
+  println!(
+    "\n{} passed, {} failed, {} unknown",
+    passed, failed, unknown
+  );
+  failed == 0
+}
+
+/// Print timings for every day that could be benchmarked, returning whether all could
+fn bench(days: &[u32], parts: &[u32], inputs: &mut Inputs, args: &Args) -> bool {
+  let mut ok = true;
+  let mut timings = Vec::new();
+  for &day in days {
+    match bench_day(day, args, parts, inputs) {
+      Ok(t) => timings.push(t),
+      Err(e) => {
+        eprintln!("Day {:02}: {}", day, e);
+        ok = false;
+      }
+    }
+  }
+  if args.json {
+    println!("{}", bench::json(args.bench, &timings));
+  } else {
+    print!("{}", bench::table(&timings));
+  }
+  ok
+}
+
+fn main() {
+  let args = parse_args(env::args().skip(1)).unwrap_or_else(|e| {
+    eprintln!("{}\n\n{}", e, USAGE);
+    process::exit(2);
+  });
+  let days: Vec<u32> = match args.day {
+    Some(day) => vec![day],
+    None => (1..=day::DAYS).collect(),
+  };
+  let parts: Vec<u32> = match args.part {
+    Some(part) => vec![part],
+    None => vec![1, 2],
+  };
+  let exit_with = |e: String| -> ! {
+    eprintln!("{}", e);
+    process::exit(1);
+  };
+  let mut inputs = load_inputs(&args).unwrap_or_else(|e| exit_with(e));
+  let ok = match (args.mode, &args.bag) {
+    (Mode::Run, Some(color)) => {
+      let queries = match args.query {
+        Some(query) => vec![query],
+        None => vec![Query::Containers, Query::Count, Query::Tree],
+      };
+      query_bags(color, &queries, &mut inputs).unwrap_or_else(|e| exit_with(e));
+      true
+    }
+    (Mode::Run, None) => run(&days, &parts, &mut inputs),
+    (Mode::Verify, _) => {
+      let answers = load_answers(&args, &inputs).unwrap_or_else(|e| exit_with(e));
+      verify(&days, &parts, &mut inputs, &answers)
+    }
+    (Mode::Bench, _) => bench(&days, &parts, &mut inputs, &args),
+  };
+  if !ok {
+    process::exit(1);
+  }
+}
@@ -1,7 +0,0 @@
-use advent_of_code_2020::day::day15::*;
-
-fn main() {
-  let input: Vec<usize> = vec![10, 16, 6, 0, 1, 17];
-  println!("Part 1: {}", part1(&input));
-  println!("Part 2: {}", part2(&input));
-}
@@ -0,0 +1,679 @@
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::fmt::{self, Display, Write as _};
+use std::io::Write;
+
+use crate::{Error, Result};
+
+/// How many registers the console has
+pub const REGISTERS: usize = 4;
+
+/// Register values, with the accumulator first
+pub type Registers = [i32; REGISTERS];
+
+/// The index of the accumulator in `Registers`
+pub const ACC: usize = 0;
+
+/// Register names in assembly, in the same order as `Registers`
+pub const REGISTER_NAMES: [&str; REGISTERS] = ["acc", "r1", "r2", "r3"];
+
+/// Something the console can execute. The interpreter only ever calls
+/// `execute`, so a new instruction set needs an implementation of this and
+/// an `InstructionSet` to assemble it from text.
+pub trait Instruction: Copy + Display {
+  /// Run against the registers, returning the offset to the next instruction
+  fn execute(self, registers: &mut Registers) -> isize;
+
+  /// The mnemonic and operand values, in the order the instruction's
+  /// `Opcode` lists its operands
+  fn encode(self) -> (&'static str, Vec<i32>);
+}
+
+/// One instruction of the handheld game console
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Command {
+  Noop(i32),
+  Jump(i32),
+  Add(i32),
+}
+
+impl Command {
+  /// The `nop`, `jmp` and `acc` instructions from the puzzle
+  pub fn instruction_set() -> InstructionSet<Command> {
+    InstructionSet::new()
+      .with("nop", &[Operand::Number], |args| Command::Noop(args[0]))
+      .with("jmp", &[Operand::Target], |args| Command::Jump(args[0]))
+      .with("acc", &[Operand::Number], |args| Command::Add(args[0]))
+  }
+
+  /// Swap `jmp` and `nop`, keeping the argument. `acc` is left as it is.
+  pub fn flipped(self) -> Self {
+    match self {
+      Command::Noop(arg) => Command::Jump(arg),
+      Command::Jump(arg) => Command::Noop(arg),
+      Command::Add(arg) => Command::Add(arg),
+    }
+  }
+
+  /// The offset to the instruction that runs after this one
+  fn offset(self) -> isize {
+    match self {
+      Command::Jump(delta) => delta as isize,
+      Command::Noop(_) | Command::Add(_) => 1,
+    }
+  }
+}
+
+impl Instruction for Command {
+  fn execute(self, registers: &mut Registers) -> isize {
+    if let Command::Add(delta) = self {
+      registers[ACC] += delta;
+    }
+    self.offset()
+  }
+
+  fn encode(self) -> (&'static str, Vec<i32>) {
+    match self {
+      Command::Noop(arg) => ("nop", vec![arg]),
+      Command::Jump(arg) => ("jmp", vec![arg]),
+      Command::Add(arg) => ("acc", vec![arg]),
+    }
+  }
+}
+
+impl Display for Command {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Command::Noop(arg) => write!(f, "nop {:+}", arg),
+      Command::Jump(arg) => write!(f, "jmp {:+}", arg),
+      Command::Add(arg) => write!(f, "acc {:+}", arg),
+    }
+  }
+}
+
+/// What kind of value an operand holds
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operand {
+  /// A signed number like `+4` or `-99`
+  Number,
+  /// A register name from `REGISTER_NAMES`, built as its index
+  Register,
+  /// Where to jump, either a signed offset or a label, built as the offset
+  Target,
+}
+
+/// How to assemble and disassemble one instruction
+pub struct Opcode<I> {
+  pub mnemonic: &'static str,
+  pub operands: &'static [Operand],
+  /// Build the instruction from one value per operand
+  pub build: fn(&[i32]) -> I,
+}
+
+/// The instructions an assembler understands, looked up by mnemonic
+pub struct InstructionSet<I> {
+  opcodes: Vec<Opcode<I>>,
+}
+
+impl<I> InstructionSet<I> {
+  pub fn new() -> Self {
+    Self {
+      opcodes: Vec::new(),
+    }
+  }
+
+  /// Add an instruction, replacing any other with the same mnemonic
+  pub fn with(
+    mut self,
+    mnemonic: &'static str,
+    operands: &'static [Operand],
+    build: fn(&[i32]) -> I,
+  ) -> Self {
+    self.opcodes.retain(|opcode| opcode.mnemonic != mnemonic);
+    self.opcodes.push(Opcode {
+      mnemonic,
+      operands,
+      build,
+    });
+    self
+  }
+
+  pub fn get(&self, mnemonic: &str) -> Option<&Opcode<I>> {
+    self
+      .opcodes
+      .iter()
+      .find(|opcode| opcode.mnemonic == mnemonic)
+  }
+
+  fn mnemonics(&self) -> String {
+    let names: Vec<String> = self
+      .opcodes
+      .iter()
+      .map(|opcode| format!("`{}`", opcode.mnemonic))
+      .collect();
+    names.join(", ")
+  }
+}
+
+impl<I> Default for InstructionSet<I> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// Parse one `<op> <signed argument>` command per line
+pub fn parse(i: &str) -> Result<Vec<Command>> {
+  assemble(i, &Command::instruction_set())
+}
+
+/// A line of assembly split into its parts, each a slice of the input
+struct Line<'a> {
+  label: Option<&'a str>,
+  mnemonic: Option<&'a str>,
+  operands: Vec<&'a str>,
+}
+
+fn split_line<'a>(input: &str, line: &'a str) -> Result<Line<'a>> {
+  let code = line.split(';').next().unwrap_or(line).trim();
+  let (label, code) = match code.split_once(':') {
+    Some((label, rest)) => {
+      let label = label.trim();
+      if !is_label(label) {
+        return Err(Error::at(input, label, "expected a label name"));
+      }
+      (Some(label), rest.trim())
+    }
+    None => (None, code),
+  };
+  let mut words = code
+    .split(|c: char| c == ',' || c.is_whitespace())
+    .filter(|word| !word.is_empty());
+  Ok(Line {
+    label,
+    mnemonic: words.next(),
+    operands: words.collect(),
+  })
+}
+
+fn is_label(name: &str) -> bool {
+  name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+    && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    && !REGISTER_NAMES.contains(&name)
+}
+
+/// Assemble a program with one instruction per line. Anything after a `;`
+/// is a comment, and a line can start with a `name:` label that jumps can
+/// use in place of an offset. A label on the last line marks the end of
+/// the program.
+///
+/// # Example
+///
+/// ```
+/// use advent_of_code_2020::console::{assemble, disassemble, Command};
+///
+/// let set = Command::instruction_set();
+/// let program = assemble(
+///   "; jump over the loop
+///        acc +1
+///   loop: acc +1 ; comments can go here too
+///        jmp end
+///        jmp loop
+///   end:",
+///   &set,
+/// )
+/// .unwrap();
+/// assert_eq!(
+///   program,
+///   vec![Command::Add(1), Command::Add(1), Command::Jump(2), Command::Jump(-2)]
+/// );
+/// assert_eq!(
+///   disassemble(&program, &set),
+///   "       acc +1\nL1:    acc +1\n       jmp L4\n       jmp L1\nL4:\n"
+/// );
+/// assert_eq!(assemble(&disassemble(&program, &set), &set).unwrap(), program);
+/// ```
+pub fn assemble<I>(input: &str, set: &InstructionSet<I>) -> Result<Vec<I>> {
+  let lines = input
+    .lines()
+    .map(|line| split_line(input, line))
+    .collect::<Result<Vec<_>>>()?;
+
+  let mut labels = HashMap::new();
+  let mut pc = 0;
+  for line in &lines {
+    if let Some(label) = line.label {
+      if labels.insert(label, pc).is_some() {
+        return Err(Error::at(input, label, "label is already defined"));
+      }
+    }
+    if line.mnemonic.is_some() {
+      pc += 1;
+    }
+  }
+
+  let mut program = Vec::with_capacity(pc);
+  for line in &lines {
+    let mnemonic = match line.mnemonic {
+      Some(mnemonic) => mnemonic,
+      None => continue,
+    };
+    let opcode = set.get(mnemonic).ok_or_else(|| {
+      Error::at(
+        input,
+        mnemonic,
+        format!("expected one of {}", set.mnemonics()),
+      )
+    })?;
+    if line.operands.len() != opcode.operands.len() {
+      return Err(Error::at(
+        input,
+        mnemonic,
+        format!(
+          "`{}` takes {} operand(s), not {}",
+          mnemonic,
+          opcode.operands.len(),
+          line.operands.len()
+        ),
+      ));
+    }
+    let values = line
+      .operands
+      .iter()
+      .zip(opcode.operands)
+      .map(|(&text, &kind)| operand_value(input, text, kind, &labels, program.len()))
+      .collect::<Result<Vec<i32>>>()?;
+    program.push((opcode.build)(&values));
+  }
+  Ok(program)
+}
+
+fn operand_value(
+  input: &str,
+  text: &str,
+  kind: Operand,
+  labels: &HashMap<&str, usize>,
+  pc: usize,
+) -> Result<i32> {
+  match kind {
+    Operand::Number => text
+      .parse()
+      .map_err(|_| Error::at(input, text, "expected a signed number")),
+    Operand::Register => REGISTER_NAMES
+      .iter()
+      .position(|&name| name == text)
+      .map(|index| index as i32)
+      .ok_or_else(|| Error::at(input, text, "expected a register name")),
+    Operand::Target => match labels.get(text) {
+      Some(&target) => Ok(target as i32 - pc as i32),
+      None if is_label(text) => Err(Error::at(input, text, "undefined label")),
+      None => text
+        .parse()
+        .map_err(|_| Error::at(input, text, "expected a signed offset or a label")),
+    },
+  }
+}
+
+/// Print a program in the form `assemble` reads, with a label at every
+/// jump target inside the program or at its end.
+pub fn disassemble<I: Instruction>(program: &[I], set: &InstructionSet<I>) -> String {
+  let encoded: Vec<_> = program
+    .iter()
+    .map(|instruction| instruction.encode())
+    .collect();
+  let target = |pc: usize, offset: i32| {
+    usize::try_from(pc as i64 + offset as i64)
+      .ok()
+      .filter(|&target| target <= program.len())
+  };
+
+  let mut targets = HashSet::new();
+  for (pc, (mnemonic, values)) in encoded.iter().enumerate() {
+    if let Some(opcode) = set.get(mnemonic) {
+      for (&kind, &value) in opcode.operands.iter().zip(values) {
+        if kind == Operand::Target {
+          targets.extend(target(pc, value));
+        }
+      }
+    }
+  }
+  let label = |pc: usize| format!("L{}:", pc);
+  let width = targets.iter().map(|&pc| label(pc).len()).max().unwrap_or(0) + 4;
+
+  let mut out = String::new();
+  for (pc, (mnemonic, values)) in encoded.iter().enumerate() {
+    let prefix = if targets.contains(&pc) {
+      label(pc)
+    } else {
+      String::new()
+    };
+    let kinds = set.get(mnemonic).map_or(&[][..], |opcode| opcode.operands);
+    let operands: Vec<String> = values
+      .iter()
+      .enumerate()
+      .map(|(idx, &value)| match kinds.get(idx) {
+        Some(Operand::Register) => REGISTER_NAMES
+          .get(value as usize)
+          .map_or_else(|| value.to_string(), |name| name.to_string()),
+        Some(Operand::Target) => {
+          target(pc, value).map_or_else(|| format!("{:+}", value), |target| format!("L{}", target))
+        }
+        _ => format!("{:+}", value),
+      })
+      .collect();
+    let _ = writeln!(
+      out,
+      "{:<width$}{} {}",
+      prefix,
+      mnemonic,
+      operands.join(", "),
+      width = width
+    );
+  }
+  if targets.contains(&program.len()) {
+    out.push_str(&label(program.len()));
+    out.push('\n');
+  }
+  out
+}
+
+/// A single executed instruction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Step<I = Command> {
+  /// Where the instruction is in the program
+  pub pc: usize,
+  pub instruction: I,
+  /// The registers after the instruction ran
+  pub registers: Registers,
+}
+
+impl<I> Step<I> {
+  pub fn acc(&self) -> i32 {
+    self.registers[ACC]
+  }
+}
+
+/// Shows the accumulator and any other register that isn't zero
+impl<I: Display> Display for Step<I> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{:>5}  {:<12}", self.pc, self.instruction.to_string())?;
+    for (idx, (name, value)) in REGISTER_NAMES.iter().zip(&self.registers).enumerate() {
+      if idx == ACC || *value != 0 {
+        write!(f, " {} = {}", name, value)?;
+      }
+    }
+    Ok(())
+  }
+}
+
+/// Somewhere for the VM to record each instruction it executes
+pub trait Trace<I> {
+  fn record(&mut self, step: &Step<I>);
+}
+
+/// Keep no trace
+impl<I> Trace<I> for () {
+  fn record(&mut self, _: &Step<I>) {}
+}
+
+/// Collect every step
+impl<I: Copy> Trace<I> for Vec<Step<I>> {
+  fn record(&mut self, step: &Step<I>) {
+    self.push(*step);
+  }
+}
+
+/// Write each step on its own line, e.g. to `std::io::stderr()`
+pub struct Printer<W: Write>(pub W);
+
+impl<I: Display, W: Write> Trace<I> for Printer<W> {
+  fn record(&mut self, step: &Step<I>) {
+    // A trace is for debugging, so a failed write shouldn't stop the program
+    let _ = writeln!(self.0, "{}", step);
+  }
+}
+
+/// Why the VM stopped running
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Exit {
+  /// Execution reached the instruction just past the end of the program
+  Terminated,
+  /// The instruction at this index was about to run a second time
+  Looped(usize),
+  /// A jump went somewhere other than the program or the end of it
+  OutOfBounds(isize),
+  /// The instruction at this index has a breakpoint and hasn't run yet
+  Breakpoint(usize),
+  /// The VM has executed as many instructions as its budget allows
+  OutOfSteps,
+}
+
+/// The handheld game console, running a program one instruction at a time.
+///
+/// # Example
+///
+/// ```
+/// use advent_of_code_2020::console::{parse, Exit, Vm};
+///
+/// let program = parse("nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6").unwrap();
+/// let mut vm = Vm::new(&program).with_trace(Vec::new());
+/// vm.add_breakpoint(6);
+/// assert_eq!(vm.run(), Exit::Breakpoint(6));
+/// assert_eq!(vm.acc(), 1);
+/// assert_eq!(vm.run(), Exit::Looped(1));
+/// assert_eq!(vm.acc(), 5);
+/// let pcs: Vec<usize> = vm.trace().iter().map(|step| step.pc).collect();
+/// assert_eq!(pcs, vec![0, 1, 2, 6, 7, 3, 4]);
+/// ```
+pub struct Vm<'a, I = Command, T = ()> {
+  program: &'a [I],
+  pc: isize,
+  registers: Registers,
+  visited: Vec<bool>,
+  breakpoints: HashSet<usize>,
+  /// Set after stopping at a breakpoint so the next `run` can get past it
+  paused: bool,
+  steps: usize,
+  budget: Option<usize>,
+  trace: T,
+}
+
+impl<'a, I: Instruction> Vm<'a, I> {
+  pub fn new(program: &'a [I]) -> Self {
+    Self {
+      program,
+      pc: 0,
+      registers: [0; REGISTERS],
+      visited: vec![false; program.len()],
+      breakpoints: HashSet::new(),
+      paused: false,
+      steps: 0,
+      budget: None,
+      trace: (),
+    }
+  }
+}
+
+impl<'a, I: Instruction, T: Trace<I>> Vm<'a, I, T> {
+  /// Record every executed instruction in `trace`
+  pub fn with_trace<U: Trace<I>>(self, trace: U) -> Vm<'a, I, U> {
+    Vm {
+      program: self.program,
+      pc: self.pc,
+      registers: self.registers,
+      visited: self.visited,
+      breakpoints: self.breakpoints,
+      paused: self.paused,
+      steps: self.steps,
+      budget: self.budget,
+      trace,
+    }
+  }
+
+  /// Stop `run` once `steps` instructions have executed in total
+  pub fn with_budget(mut self, steps: usize) -> Self {
+    self.budget = Some(steps);
+    self
+  }
+
+  /// Change the step budget, or remove it with `None`
+  pub fn set_budget(&mut self, budget: Option<usize>) {
+    self.budget = budget;
+  }
+
+  /// How many instructions have executed since the start or the last reset
+  pub fn steps(&self) -> usize {
+    self.steps
+  }
+
+  pub fn trace(&self) -> &T {
+    &self.trace
+  }
+
+  pub fn into_trace(self) -> T {
+    self.trace
+  }
+
+  pub fn pc(&self) -> isize {
+    self.pc
+  }
+
+  pub fn acc(&self) -> i32 {
+    self.registers[ACC]
+  }
+
+  pub fn registers(&self) -> &Registers {
+    &self.registers
+  }
+
+  /// Stop `run` before the instruction at `pc` executes
+  pub fn add_breakpoint(&mut self, pc: usize) {
+    self.breakpoints.insert(pc);
+  }
+
+  pub fn remove_breakpoint(&mut self, pc: usize) {
+    self.breakpoints.remove(&pc);
+  }
+
+  /// Go back to the start of the program, keeping breakpoints
+  pub fn reset(&mut self) {
+    self.pc = 0;
+    self.registers = [0; REGISTERS];
+    self.visited.iter_mut().for_each(|seen| *seen = false);
+    self.paused = false;
+    self.steps = 0;
+  }
+
+  /// The instruction about to run, if the program counter is in the program
+  pub fn current(&self) -> Option<(usize, I)> {
+    let pc = usize::try_from(self.pc).ok()?;
+    self.program.get(pc).map(|&instruction| (pc, instruction))
+  }
+
+  /// Whether the instruction about to run has run before
+  fn looping(&self) -> bool {
+    self.current().is_some_and(|(pc, _)| self.visited[pc])
+  }
+
+  /// Run the current instruction, or return `None` if the program counter
+  /// isn't in the program. Breakpoints and loops don't stop a single step.
+  pub fn step(&mut self) -> Option<Step<I>> {
+    let (pc, instruction) = self.current()?;
+    self.visited[pc] = true;
+    self.pc += instruction.execute(&mut self.registers);
+    self.paused = false;
+    self.steps += 1;
+    let step = Step {
+      pc,
+      instruction,
+      registers: self.registers,
+    };
+    self.trace.record(&step);
+    Some(step)
+  }
+
+  /// Step until the program ends, an instruction is about to run a second
+  /// time, a breakpoint is reached or the step budget runs out.
+  pub fn run(&mut self) -> Exit {
+    loop {
+      let pc = match self.current() {
+        Some((pc, _)) => pc,
+        None if self.pc == self.program.len() as isize => return Exit::Terminated,
+        None => return Exit::OutOfBounds(self.pc),
+      };
+      if self.visited[pc] {
+        return Exit::Looped(pc);
+      }
+      if self.breakpoints.contains(&pc) && !self.paused {
+        self.paused = true;
+        return Exit::Breakpoint(pc);
+      }
+      if self.budget.is_some_and(|budget| self.steps >= budget) {
+        return Exit::OutOfSteps;
+      }
+      self.step();
+    }
+  }
+}
+
+/// Which instructions lead to the end of the program if execution starts
+/// there, found by walking the control flow graph backwards from the end.
+pub fn terminating(program: &[Command]) -> Vec<bool> {
+  let end = program.len();
+  let mut predecessors = vec![Vec::new(); end + 1];
+  for (pc, command) in program.iter().enumerate() {
+    let next = pc as isize + command.offset();
+    if (0..=end as isize).contains(&next) {
+      predecessors[next as usize].push(pc);
+    }
+  }
+  let mut reaches_end = vec![false; end + 1];
+  reaches_end[end] = true;
+  let mut queue = vec![end];
+  while let Some(pc) = queue.pop() {
+    for &previous in &predecessors[pc] {
+      if !reaches_end[previous] {
+        reaches_end[previous] = true;
+        queue.push(previous);
+      }
+    }
+  }
+  reaches_end.truncate(end);
+  reaches_end
+}
+
+/// Find the single `jmp` or `nop` to flip so the program terminates.
+/// Returns `None` if it already terminates or no one flip fixes it.
+///
+/// Only an instruction the broken program actually runs can make a
+/// difference, and flipping it works when its new successor is one of the
+/// instructions that already reach the end.
+///
+/// # Example
+///
+/// ```
+/// use advent_of_code_2020::console::{parse, repair};
+///
+/// let program = parse("nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6").unwrap();
+/// assert_eq!(repair(&program), Some(7));
+/// ```
+pub fn repair(program: &[Command]) -> Option<usize> {
+  let reaches_end = terminating(program);
+  if reaches_end.first() != Some(&false) {
+    return None;
+  }
+  let end = program.len() as isize;
+  let mut vm = Vm::new(program);
+  while let Some(step) = vm.step() {
+    let flipped = step.instruction.flipped();
+    if flipped != step.instruction {
+      let next = step.pc as isize + flipped.offset();
+      if next == end || (0..end).contains(&next) && reaches_end[next as usize] {
+        return Some(step.pc);
+      }
+    }
+    if vm.looping() {
+      break;
+    }
+  }
+  None
+}
@@ -1,3 +1,38 @@
+use crate::{Error, Result, Solution};
+
+pub struct Day01;
+
+impl Solution for Day01 {
+  type Input = Vec<i32>;
+  type Part1 = i32;
+  type Part2 = i32;
+
+  fn parse(input: &str) -> Result<Vec<i32>> {
+    parse(input)
+  }
+
+  fn part1(input: &Vec<i32>) -> i32 {
+    part1(input)
+  }
+
+  fn part2(input: &Vec<i32>) -> i32 {
+    part2(input)
+  }
+}
+
+/// Parse one expense entry per line
+pub fn parse(i: &str) -> Result<Vec<i32>> {
+  i.lines()
+    .filter(|line| !line.trim().is_empty())
+    .map(|line| {
+      line
+        .trim()
+        .parse()
+        .map_err(|_| Error::at(i, line, "expected a number"))
+    })
+    .collect()
+}
+
 /// Find the two items that sum to 2020 and return their product
 ///
 /// # Example
@@ -9,13 +44,10 @@
 /// let result = part1(&input);
 /// assert_eq!(result, 514579);
 /// ```
-pub fn part1(i: &Vec<i32>) -> i32 {
+pub fn part1(i: &[i32]) -> i32 {
   i.iter()
     .copied()
-    .find_map(|j| match i.iter().copied().find(|k| j + k == 2020) {
-      Some(k) => Some(j * k),
-      None => None,
-    })
+    .find_map(|j| i.iter().copied().find(|k| j + k == 2020).map(|k| j * k))
     .unwrap_or(0)
 }
 
@@ -30,20 +62,19 @@ pub fn part1(i: &Vec<i32>) -> i32 {
 /// let result = part2(&input);
 /// assert_eq!(result, 241861950);
 /// ```
-pub fn part2(i: &Vec<i32>) -> i32 {
+pub fn part2(i: &[i32]) -> i32 {
   i.iter()
     .copied()
     .find_map(|j| {
-      match i
-        .iter()
+      i.iter()
         .copied()
-        .find_map(|k| match i.iter().copied().find(|l| j + k + l == 2020) {
-          Some(l) => Some((k, l)),
-          None => None,
-        }) {
-        Some((k, l)) => Some(j * k * l),
-        None => None,
-      }
+        .find_map(|k| {
+          i.iter()
+            .copied()
+            .find(|l| j + k + l == 2020)
+            .map(|l| (k, l))
+        })
+        .map(|(k, l)| j * k * l)
     })
     .unwrap_or(0)
 }
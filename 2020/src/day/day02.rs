@@ -1,47 +1,85 @@
-use std::convert::TryInto;
+use crate::{Error, Result, Solution};
+
+pub struct Day02;
+
+impl Solution for Day02 {
+  type Input = Vec<PasswordEntry>;
+  type Part1 = usize;
+  type Part2 = usize;
+
+  fn parse(input: &str) -> Result<Vec<PasswordEntry>> {
+    parse(input)
+  }
+
+  fn part1(input: &Vec<PasswordEntry>) -> usize {
+    part1(input)
+  }
+
+  fn part2(input: &Vec<PasswordEntry>) -> usize {
+    part2(input)
+  }
+}
+
+/// A password along with the policy that was in effect when it was set.
+/// `1-3 a: abcde` gives `first` 1, `second` 3 and `letter` 'a'.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PasswordEntry {
+  first: usize,
+  second: usize,
+  letter: char,
+  password: String,
+}
+
+/// Parse one `min-max letter: password` entry per line
+pub fn parse(i: &str) -> Result<Vec<PasswordEntry>> {
+  i.lines()
+    .filter(|line| !line.trim().is_empty())
+    .map(|line| parse_entry(i, line))
+    .collect()
+}
+
+fn parse_entry(i: &str, line: &str) -> Result<PasswordEntry> {
+  let (rule, password) = line
+    .split_once(':')
+    .ok_or_else(|| Error::at(i, line, "expected `<policy>: <password>`"))?;
+  let (range, letter) = rule
+    .split_once(' ')
+    .ok_or_else(|| Error::at(i, rule, "expected `<min>-<max> <letter>`"))?;
+  let (first, second) = range
+    .split_once('-')
+    .ok_or_else(|| Error::at(i, range, "expected `<min>-<max>`"))?;
+  let number = |s: &str| s.parse().map_err(|_| Error::at(i, s, "expected a number"));
+  let mut letter_chars = letter.chars();
+  let letter = match (letter_chars.next(), letter_chars.next()) {
+    (Some(c), None) => c,
+    _ => return Err(Error::at(i, letter, "expected a single letter")),
+  };
+  Ok(PasswordEntry {
+    first: number(first)?,
+    second: number(second)?,
+    letter,
+    password: password.trim().into(),
+  })
+}
 
 /// Find the valid passwords from the input.
 ///
 /// # Example
 ///
 /// ```
-/// use advent_of_code_2020::day::day02::part1;
+/// use advent_of_code_2020::day::day02::*;
 ///
-/// let input = vec!["1-3 a: abcde", "1-3 b: cdefg", "2-9 c: ccccccccc"];
+/// let input = parse("1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc").unwrap();
 /// let result = part1(&input);
 /// assert_eq!(result, 2);
 /// ```
-pub fn part1(i: &Vec<&str>) -> usize {
-  parse(i)
-    .iter()
-    .filter_map(|(rule, password)| {
-      if is_valid_one((rule, password)) {
-        Some(password)
-      } else {
-        None
-      }
-    })
-    .count()
-}
-
-fn parse<'a>(i: &Vec<&'a str>) -> Vec<(&'a str, &'a str)> {
-  i.iter()
-    .map(|s| {
-      let parts: Vec<&str> = s.split(":").collect();
-      (parts[0], parts[1].trim())
-    })
-    .collect()
+pub fn part1(i: &[PasswordEntry]) -> usize {
+  i.iter().filter(|entry| is_valid_one(entry)).count()
 }
 
-fn is_valid_one(i: (&str, &str)) -> bool {
-  let (rule, password) = i;
-  let parts: Vec<&str> = rule.split(" ").collect();
-  let counts: Vec<i32> = parts[0].split("-").filter_map(|s| s.parse().ok()).collect();
-  let min = counts[0];
-  let max = counts[1];
-  let char = parts[1].trim();
-  let matches: i32 = password.matches(&char).count().try_into().unwrap();
-  matches >= min && matches <= max
+fn is_valid_one(entry: &PasswordEntry) -> bool {
+  let matches = entry.password.matches(entry.letter).count();
+  (entry.first..=entry.second).contains(&matches)
 }
 
 /// Find the valid passwords from the input.
@@ -49,35 +87,24 @@ fn is_valid_one(i: (&str, &str)) -> bool {
 /// # Example
 ///
 /// ```
-/// use advent_of_code_2020::day::day02::part2;
+/// use advent_of_code_2020::day::day02::*;
 ///
-/// let input = vec!["1-3 a: abcde", "1-3 b: cdefg", "2-9 c: ccccccccc"];
+/// let input = parse("1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc").unwrap();
 /// let result = part2(&input);
 /// assert_eq!(result, 1);
 /// ```
-pub fn part2(i: &Vec<&str>) -> usize {
-  parse(i)
-    .iter()
-    .filter_map(|(rule, password)| {
-      if is_valid_two((rule, password)) {
-        Some(password)
-      } else {
-        None
-      }
-    })
-    .count()
+pub fn part2(i: &[PasswordEntry]) -> usize {
+  i.iter().filter(|entry| is_valid_two(entry)).count()
 }
 
-fn is_valid_two(i: (&str, &str)) -> bool {
-  let (rule, password) = i;
-  let parts: Vec<&str> = rule.split(" ").collect();
-  let indices: Vec<usize> = parts[0].split("-").filter_map(|s| s.parse().ok()).collect();
-  let char = parts[1].trim();
-  match (
-    password.get(indices[0] - 1..indices[0]),
-    password.get(indices[1] - 1..indices[1]),
-  ) {
-    (Some(char1), Some(char2)) => char1 != char2 && (char1 == char || char2 == char),
+fn is_valid_two(entry: &PasswordEntry) -> bool {
+  let at = |position: usize| {
+    position
+      .checked_sub(1)
+      .and_then(|idx| entry.password.chars().nth(idx))
+  };
+  match (at(entry.first), at(entry.second)) {
+    (Some(char1), Some(char2)) => (char1 == entry.letter) != (char2 == entry.letter),
     _ => false,
   }
 }
@@ -1,3 +1,26 @@
+use crate::grid::Grid;
+use crate::{Result, Solution};
+
+pub struct Day03;
+
+impl Solution for Day03 {
+  type Input = Grid<MapGrid>;
+  type Part1 = i32;
+  type Part2 = i32;
+
+  fn parse(input: &str) -> Result<Grid<MapGrid>> {
+    parse(input)
+  }
+
+  fn part1(input: &Grid<MapGrid>) -> i32 {
+    part1(input)
+  }
+
+  fn part2(input: &Grid<MapGrid>) -> i32 {
+    part2(input)
+  }
+}
+
 /// Count the number of trees denoted by # that would be encountered
 /// moving from top left to the bottom by 3 x and y 1 each step
 ///
@@ -6,7 +29,7 @@
 /// ```
 /// use advent_of_code_2020::day::day03::*;
 ///
-/// let input = "..##.......
+/// let input = parse("..##.......
 ///#...#...#..
 ///.#....#..#.
 ///..#.#...#.#
@@ -16,14 +39,12 @@
 ///.#........#
 ///#.##...#...
 ///#...##....#
-///.#..#...#.#".to_string();
-/// println!("{}", input);
+///.#..#...#.#").unwrap();
 /// let result = part1(&input);
 /// assert_eq!(result, 7);
 /// ```
-pub fn part1(i: &String) -> i32 {
-  let map_grid = parse(i);
-  count_trees(&map_grid, 3, 1)
+pub fn part1(map_grid: &Grid<MapGrid>) -> i32 {
+  count_trees(map_grid, 3, 1)
 }
 
 #[derive(Debug, PartialEq)]
@@ -32,35 +53,21 @@ pub enum MapGrid {
   Tree,
 }
 
-fn parse(i: &String) -> Vec<Vec<MapGrid>> {
-  i.split("\n")
-    .map(|s| {
-      s.chars()
-        .filter_map(|c| match c {
-          '.' => Some(MapGrid::Open),
-          '#' => Some(MapGrid::Tree),
-          _ => None,
-        })
-        .collect()
-    })
-    .collect()
+/// Parse the map where `.` is open ground and `#` is a tree
+pub fn parse(i: &str) -> Result<Grid<MapGrid>> {
+  Grid::parse(i, |c| match c {
+    '.' => Some(MapGrid::Open),
+    '#' => Some(MapGrid::Tree),
+    _ => None,
+  })
 }
 
-fn count_trees(map_grid: &Vec<Vec<MapGrid>>, step_x: usize, step_y: usize) -> i32 {
-  map_grid
-    .iter()
+fn count_trees(map_grid: &Grid<MapGrid>, step_x: usize, step_y: usize) -> i32 {
+  (0..map_grid.height())
     .step_by(step_y)
     .enumerate()
-    .fold(0, |tree_count, (y, row)| {
-      row
-        .iter()
-        .cycle()
-        .nth(y * step_x)
-        .map_or(tree_count, |m| match m {
-          MapGrid::Tree => tree_count + 1,
-          MapGrid::Open => tree_count,
-        })
-    })
+    .filter(|&(step, y)| *map_grid.get_wrapping(step * step_x, y) == MapGrid::Tree)
+    .count() as i32
 }
 
 /// Calculate the product of trees moving from the following slopes:
@@ -75,7 +82,7 @@ fn count_trees(map_grid: &Vec<Vec<MapGrid>>, step_x: usize, step_y: usize) -> i3
 /// ```
 /// use advent_of_code_2020::day::day03::*;
 ///
-/// let input = "..##.......
+/// let input = parse("..##.......
 ///#...#...#..
 ///.#....#..#.
 ///..#.#...#.#
@@ -85,15 +92,13 @@ fn count_trees(map_grid: &Vec<Vec<MapGrid>>, step_x: usize, step_y: usize) -> i3
 ///.#........#
 ///#.##...#...
 ///#...##....#
-///.#..#...#.#".to_string();
-/// println!("{}", input);
+///.#..#...#.#").unwrap();
 /// let result = part2(&input);
 /// assert_eq!(result, 336);
 /// ```
-pub fn part2(i: &String) -> i32 {
-  let map_grid = parse(i);
+pub fn part2(map_grid: &Grid<MapGrid>) -> i32 {
   [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)]
     .iter()
-    .map(|(step_x, step_y)| count_trees(&map_grid, *step_x, *step_y))
+    .map(|(step_x, step_y)| count_trees(map_grid, *step_x, *step_y))
     .product()
 }
@@ -1,5 +1,27 @@
 use regex::Regex;
 
+use crate::{Error, Result, Solution};
+
+pub struct Day04;
+
+impl Solution for Day04 {
+  type Input = Vec<DocumentBatch>;
+  type Part1 = usize;
+  type Part2 = usize;
+
+  fn parse(input: &str) -> Result<Vec<DocumentBatch>> {
+    parse(input)
+  }
+
+  fn part1(input: &Vec<DocumentBatch>) -> usize {
+    part1(input)
+  }
+
+  fn part2(input: &Vec<DocumentBatch>) -> usize {
+    part2(input)
+  }
+}
+
 /// Count the number of valid batches
 /// only the cid field is optional
 ///
@@ -8,7 +30,7 @@ use regex::Regex;
 /// ```
 /// use advent_of_code_2020::day::day04::*;
 ///
-/// let input = "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
+/// let input = parse("ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
 ///byr:1937 iyr:2017 cid:147 hgt:183cm
 ///
 ///iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884
@@ -20,12 +42,11 @@ use regex::Regex;
 ///hgt:179cm
 ///
 ///hcl:#cfa07d eyr:2025 pid:166559648
-///iyr:2011 ecl:brn hgt:59in".to_string();
+///iyr:2011 ecl:brn hgt:59in").unwrap();
 /// let result = part1(&input);
 /// assert_eq!(result, 2);
 /// ```
-pub fn part1(i: &String) -> usize {
-  let docs = parse(i);
+pub fn part1(docs: &[DocumentBatch]) -> usize {
   docs
     .iter()
     .filter(|d| {
@@ -37,7 +58,7 @@ pub fn part1(i: &String) -> usize {
 }
 
 #[derive(Debug, Default)]
-struct DocumentBatch {
+pub struct DocumentBatch {
   byr: Option<String>,
   iyr: Option<String>,
   eyr: Option<String>,
@@ -45,54 +66,47 @@ struct DocumentBatch {
   hcl: Option<String>,
   ecl: Option<String>,
   pid: Option<String>,
-  cid: Option<String>,
 }
 
-fn parse(i: &String) -> Vec<DocumentBatch> {
+/// Parse the blank line separated batches of `key:value` fields
+pub fn parse(i: &str) -> Result<Vec<DocumentBatch>> {
   i.split("\n\n")
     .map(|batch| {
       batch
-        .replace("\n", " ")
-        .split(" ")
-        .fold(Default::default(), |doc, fields| {
-          let key_value: Vec<&str> = fields.split(":").collect();
-          if key_value.len() != 2 {
-            return doc;
-          }
-          match (key_value[0], key_value[1]) {
-            ("byr", val) => DocumentBatch {
+        .split_whitespace()
+        .try_fold(DocumentBatch::default(), |doc, field| {
+          match field.split_once(':') {
+            Some(("byr", val)) => Ok(DocumentBatch {
               byr: Some(val.into()),
               ..doc
-            },
-            ("iyr", val) => DocumentBatch {
+            }),
+            Some(("iyr", val)) => Ok(DocumentBatch {
               iyr: Some(val.into()),
               ..doc
-            },
-            ("eyr", val) => DocumentBatch {
+            }),
+            Some(("eyr", val)) => Ok(DocumentBatch {
               eyr: Some(val.into()),
               ..doc
-            },
-            ("hgt", val) => DocumentBatch {
+            }),
+            Some(("hgt", val)) => Ok(DocumentBatch {
               hgt: Some(val.into()),
               ..doc
-            },
-            ("hcl", val) => DocumentBatch {
+            }),
+            Some(("hcl", val)) => Ok(DocumentBatch {
               hcl: Some(val.into()),
               ..doc
-            },
-            ("ecl", val) => DocumentBatch {
+            }),
+            Some(("ecl", val)) => Ok(DocumentBatch {
               ecl: Some(val.into()),
               ..doc
-            },
-            ("pid", val) => DocumentBatch {
+            }),
+            Some(("pid", val)) => Ok(DocumentBatch {
               pid: Some(val.into()),
               ..doc
-            },
-            ("cid", val) => DocumentBatch {
-              cid: Some(val.into()),
-              ..doc
-            },
-            _ => doc,
+            }),
+            Some(("cid", _)) => Ok(doc),
+            Some(_) => Err(Error::at(i, field, "unknown field")),
+            None => Err(Error::at(i, field, "expected `key:value`")),
           }
         })
     })
@@ -116,7 +130,7 @@ fn parse(i: &String) -> Vec<DocumentBatch> {
 /// ```
 /// use advent_of_code_2020::day::day04::*;
 ///
-/// let valid = "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980
+/// let valid = parse("pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980
 ///hcl:#623a2f
 ///
 ///eyr:2029 ecl:blu cid:129 byr:1989
@@ -127,9 +141,9 @@ fn parse(i: &String) -> Vec<DocumentBatch> {
 ///pid:545766238 ecl:hzl
 ///eyr:2022
 ///
-///iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719".to_string();
+///iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719").unwrap();
 ///
-/// let invalid = "eyr:1972 cid:100
+/// let invalid = parse("eyr:1972 cid:100
 ///hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926
 ///
 ///iyr:2019
@@ -141,13 +155,12 @@ fn parse(i: &String) -> Vec<DocumentBatch> {
 ///
 ///hgt:59cm ecl:zzz
 ///eyr:2038 hcl:74454a iyr:2023
-///pid:3556412378 byr:2007".to_string();
+///pid:3556412378 byr:2007").unwrap();
 ///
 /// assert_eq!(part2(&valid), 4);
 /// assert_eq!(part2(&invalid), 0);
 /// ```
-pub fn part2(i: &String) -> usize {
-  let docs = parse(i);
+pub fn part2(docs: &[DocumentBatch]) -> usize {
   docs.iter().filter(|d| is_valid(d)).count()
 }
 
@@ -161,7 +174,6 @@ fn is_valid(doc: &DocumentBatch) -> bool {
       hcl: Some(hcl),
       ecl: Some(ecl),
       pid: Some(pid),
-      cid: _,
     } => {
       is_valid_year(byr, 1920, 2002)
         && is_valid_year(iyr, 2010, 2020)
@@ -177,7 +189,7 @@ fn is_valid(doc: &DocumentBatch) -> bool {
 
 fn is_valid_year(i: &str, min: i32, max: i32) -> bool {
   let year: i32 = i.parse().ok().unwrap_or(0);
-  year >= min && year <= max
+  (min..=max).contains(&year)
 }
 
 fn is_valid_hgt(i: &str) -> bool {
@@ -186,9 +198,10 @@ fn is_valid_hgt(i: &str) -> bool {
     let measurement: i32 = caps.get(1).unwrap().as_str().parse().ok().unwrap_or(0);
     let units = caps.get(2).unwrap().as_str();
     if units == "cm" {
-      return measurement >= 150 && measurement <= 193;
+      (150..=193).contains(&measurement)
+    } else {
+      (59..=76).contains(&measurement)
     }
-    return measurement >= 59 && measurement <= 76;
   } else {
     false
   }
@@ -1,3 +1,25 @@
+use crate::{Error, Result, Solution};
+
+pub struct Day05;
+
+impl Solution for Day05 {
+  type Input = Vec<Seat>;
+  type Part1 = i32;
+  type Part2 = i32;
+
+  fn parse(input: &str) -> Result<Vec<Seat>> {
+    parse(input)
+  }
+
+  fn part1(input: &Vec<Seat>) -> i32 {
+    part1(input)
+  }
+
+  fn part2(input: &Vec<Seat>) -> i32 {
+    part2(input)
+  }
+}
+
 /// Given the input find the highest seat id
 ///
 /// # Example
@@ -5,20 +27,20 @@
 /// ```
 /// use advent_of_code_2020::day::day05::*;
 ///
-/// let input = "BFFFBBFRRR
+/// let input = parse("BFFFBBFRRR
 ///FFFBBBFRRR
-///BBFFBBFRLL".to_string();
+///BBFFBBFRLL").unwrap();
 /// let result = part1(&input);
 /// assert_eq!(result, 820);
 /// ```
-pub fn part1(i: &String) -> i32 {
-  parse(i)
+pub fn part1(seats: &[Seat]) -> i32 {
+  seats
     .iter()
     .fold(0, |max, seat| if max > seat.id() { max } else { seat.id() })
 }
 
 #[derive(Debug)]
-struct Seat {
+pub struct Seat {
   row: i32,
   column: i32,
 }
@@ -41,68 +63,90 @@ struct PendingSeat {
   column: Range,
 }
 
-fn parse(i: &String) -> Vec<Seat> {
-  i.split("\n")
-    .map(|line| {
-      line.chars().fold(
-        PendingSeat {
-          row: Range { min: 0, max: 127 },
-          column: Range { min: 0, max: 7 },
+/// Parse the binary space partitioned boarding passes, one per line
+pub fn parse(i: &str) -> Result<Vec<Seat>> {
+  i.lines()
+    .filter(|line| !line.is_empty())
+    .map(|line| validate_boarding_pass(i, line).map(decode_seat))
+    .collect()
+}
+
+fn decode_seat(line: &str) -> Seat {
+  let ps = line.chars().fold(
+    PendingSeat {
+      row: Range { min: 0, max: 127 },
+      column: Range { min: 0, max: 7 },
+    },
+    |pending_seat, char| match char {
+      'F' => PendingSeat {
+        row: Range {
+          min: pending_seat.row.min,
+          max: (pending_seat.row.max
+            - ((pending_seat.row.max - pending_seat.row.min) as f32 / 2.0).floor() as i32),
         },
-        |pending_seat, char| match char {
-          'F' => PendingSeat {
-            row: Range {
-              min: pending_seat.row.min,
-              max: (pending_seat.row.max
-                - ((pending_seat.row.max - pending_seat.row.min) as f32 / 2.0).floor() as i32),
-            },
-            ..pending_seat
-          },
-          'B' => PendingSeat {
-            row: Range {
-              min: (pending_seat.row.min
-                + ((pending_seat.row.max - pending_seat.row.min) as f32 / 2.0).ceil() as i32),
-              max: pending_seat.row.max,
-            },
-            ..pending_seat
-          },
-          'L' => PendingSeat {
-            column: Range {
-              min: pending_seat.column.min,
-              max: (pending_seat.column.max
-                - ((pending_seat.column.max - pending_seat.column.min) as f32 / 2.0).floor()
-                  as i32),
-            },
-            ..pending_seat
-          },
-          'R' => PendingSeat {
-            column: Range {
-              min: (pending_seat.column.min
-                + ((pending_seat.column.max - pending_seat.column.min) as f32 / 2.0).ceil() as i32),
-              max: pending_seat.column.max,
-            },
-            ..pending_seat
-          },
-          _ => pending_seat,
+        ..pending_seat
+      },
+      'B' => PendingSeat {
+        row: Range {
+          min: (pending_seat.row.min
+            + ((pending_seat.row.max - pending_seat.row.min) as f32 / 2.0).ceil() as i32),
+          max: pending_seat.row.max,
         },
-      )
-    })
-    .map(|ps| Seat {
-      row: ps.row.min,
-      column: ps.column.min,
-    })
-    .collect()
+        ..pending_seat
+      },
+      'L' => PendingSeat {
+        column: Range {
+          min: pending_seat.column.min,
+          max: (pending_seat.column.max
+            - ((pending_seat.column.max - pending_seat.column.min) as f32 / 2.0).floor() as i32),
+        },
+        ..pending_seat
+      },
+      'R' => PendingSeat {
+        column: Range {
+          min: (pending_seat.column.min
+            + ((pending_seat.column.max - pending_seat.column.min) as f32 / 2.0).ceil() as i32),
+          max: pending_seat.column.max,
+        },
+        ..pending_seat
+      },
+      _ => pending_seat,
+    },
+  );
+  Seat {
+    row: ps.row.min,
+    column: ps.column.min,
+  }
+}
+
+/// Boarding passes are 7 `F`/`B` row characters followed by 3 `L`/`R` column characters
+fn validate_boarding_pass<'a>(i: &str, line: &'a str) -> Result<&'a str> {
+  if line.chars().count() != 10 {
+    return Err(Error::at(i, line, "expected 10 characters"));
+  }
+  match line
+    .char_indices()
+    .enumerate()
+    .find(|&(n, (_, c))| !matches!((n < 7, c), (true, 'F' | 'B') | (false, 'L' | 'R')))
+  {
+    Some((_, (idx, c))) => Err(Error::at(
+      i,
+      &line[idx..idx + c.len_utf8()],
+      "expected F/B then L/R",
+    )),
+    None => Ok(line),
+  }
 }
 
 /// Find your seat id, can skip front and back rows
-pub fn part2(i: &String) -> i32 {
-  let mut seat_ids: Vec<i32> = parse(i).iter().map(|s| s.id()).collect();
-  seat_ids.sort();
+pub fn part2(seats: &[Seat]) -> i32 {
+  let mut seat_ids: Vec<i32> = seats.iter().map(|s| s.id()).collect();
+  seat_ids.sort_unstable();
   seat_ids
     .iter()
     .enumerate()
-    .find_map(|(idx, seat_id)| match seat_ids[idx + 1] {
-      next_id if seat_id + 2 == next_id => Some(seat_id + 1),
+    .find_map(|(idx, seat_id)| match seat_ids.get(idx + 1) {
+      Some(next_id) if seat_id + 2 == *next_id => Some(seat_id + 1),
       _ => None,
     })
     .unwrap_or_default()
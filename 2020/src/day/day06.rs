@@ -1,5 +1,27 @@
 use std::collections::HashSet;
 
+use crate::{Error, Result, Solution};
+
+pub struct Day06;
+
+impl Solution for Day06 {
+  type Input = Vec<Group>;
+  type Part1 = usize;
+  type Part2 = usize;
+
+  fn parse(input: &str) -> Result<Vec<Group>> {
+    parse(input)
+  }
+
+  fn part1(input: &Vec<Group>) -> usize {
+    part1(input)
+  }
+
+  fn part2(input: &Vec<Group>) -> usize {
+    part2(input)
+  }
+}
+
 /// Each line is questions a-z that were answered YES per individual.
 /// Goups are separated by a blank line.
 ///
@@ -10,7 +32,7 @@ use std::collections::HashSet;
 /// ```
 /// use advent_of_code_2020::day::day06::*;
 ///
-/// let input = "abc
+/// let input = parse("abc
 ///
 ///a
 ///b
@@ -24,23 +46,50 @@ use std::collections::HashSet;
 ///a
 ///a
 ///
-///b".to_string();
+///b").unwrap();
 /// let result = part1(&input);
 /// assert_eq!(result, 11);
 /// ```
-pub fn part1(i: &String) -> usize {
-  let group_sets: Vec<HashSet<char>> = i
-    .split("\n\n")
-    .into_iter()
-    .map(|s| {
-      let mut set = HashSet::new();
-      s.chars().filter(|c| c.is_alphabetic()).for_each(|c| {
-        set.insert(c);
-      });
-      set
+pub fn part1(groups: &[Group]) -> usize {
+  groups
+    .iter()
+    .map(|group| {
+      group
+        .iter()
+        .fold(HashSet::new(), |set, individual| &set | individual)
+        .len()
+    })
+    .sum()
+}
+
+/// Yes answers for each individual in a group
+pub type Group = Vec<HashSet<char>>;
+
+/// Parse the blank line separated groups of individual answers
+pub fn parse(i: &str) -> Result<Vec<Group>> {
+  i.split("\n\n")
+    .map(|group| {
+      group
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| parse_answers(i, line))
+        .collect()
+    })
+    .collect()
+}
+
+fn parse_answers(i: &str, line: &str) -> Result<HashSet<char>> {
+  line
+    .char_indices()
+    .map(|(idx, c)| match c {
+      'a'..='z' => Ok(c),
+      _ => Err(Error::at(
+        i,
+        &line[idx..idx + c.len_utf8()],
+        "expected a question `a`-`z`",
+      )),
     })
-    .collect();
-  group_sets.iter().map(|g| g.len()).sum()
+    .collect()
 }
 
 /// Each line is questions a-z that were answered YES per individual.
@@ -53,7 +102,7 @@ pub fn part1(i: &String) -> usize {
 /// ```
 /// use advent_of_code_2020::day::day06::*;
 ///
-/// let input = "abc
+/// let input = parse("abc
 ///
 ///a
 ///b
@@ -67,24 +116,19 @@ pub fn part1(i: &String) -> usize {
 ///a
 ///a
 ///
-///b".to_string();
+///b").unwrap();
 /// let result = part2(&input);
 /// assert_eq!(result, 6);
 /// ```
-pub fn part2(i: &String) -> usize {
-  let group_sets: Vec<usize> = i
-    .split("\n\n")
-    .map(|s| {
-      let mut set = HashSet::new();
-      s.chars().filter(|c| c.is_alphabetic()).for_each(|c| {
-        set.insert(c);
-      });
-      let individuals: Vec<HashSet<char>> = s.split("\n").map(|s| s.chars().collect()).collect();
-      set
+pub fn part2(groups: &[Group]) -> usize {
+  groups
+    .iter()
+    .map(|group| {
+      let everyone: HashSet<char> = group.iter().flatten().copied().collect();
+      everyone
         .iter()
-        .filter(|c| individuals.iter().all(|chars| chars.contains(c)))
+        .filter(|c| group.iter().all(|chars| chars.contains(c)))
         .count()
     })
-    .collect();
-  group_sets.iter().sum()
+    .sum()
 }
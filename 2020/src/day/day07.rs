@@ -1,5 +1,35 @@
-use regex::Regex;
-use std::collections::HashSet;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::{Error, Result, Solution};
+
+pub struct Day07;
+
+impl Solution for Day07 {
+  type Input = Bags;
+  type Part1 = usize;
+  type Part2 = usize;
+
+  fn parse(input: &str) -> Result<Bags> {
+    let bags = parse(input)?;
+    if bags.id("shiny gold").is_none() {
+      return Err(Error::at(
+        input,
+        &input[input.len()..],
+        "expected a rule for shiny gold bags",
+      ));
+    }
+    Ok(bags)
+  }
+
+  fn part1(input: &Bags) -> usize {
+    part1(input)
+  }
+
+  fn part2(input: &Bags) -> usize {
+    part2(input)
+  }
+}
 
 /// Each line is a rule about what bags a certain color bag may contain.
 /// You have a shiny gold bag. Find how many bags may contain a shiny gold bag.
@@ -9,7 +39,7 @@ use std::collections::HashSet;
 /// ```
 /// use advent_of_code_2020::day::day07::*;
 ///
-/// let input = "light red bags contain 1 bright white bag, 2 muted yellow bags.
+/// let input = parse("light red bags contain 1 bright white bag, 2 muted yellow bags.
 ///dark orange bags contain 3 bright white bags, 4 muted yellow bags.
 ///bright white bags contain 1 shiny gold bag.
 ///muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.
@@ -17,77 +47,279 @@ use std::collections::HashSet;
 ///dark olive bags contain 3 faded blue bags, 4 dotted black bags.
 ///vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.
 ///faded blue bags contain no other bags.
-///dotted black bags contain no other bags.".to_string();
+///dotted black bags contain no other bags.").unwrap();
 /// let result = part1(&input);
 /// assert_eq!(result, 4);
 /// ```
-pub fn part1(i: &String) -> usize {
-  let rules = parse_rules(i);
-  count_can_contain("shiny gold".into(), &rules)
+pub fn part1(bags: &Bags) -> usize {
+  containers_of(bags, "shiny gold")
+    .expect("there is a rule for shiny gold bags")
+    .len()
 }
 
-#[derive(Debug)]
-struct BagRule {
-  color: String,
-  count: usize,
+/// The bag rules as a graph with a node for each colour and an edge from
+/// each bag to each bag it holds. Rules can't be cyclic, so the number of
+/// bags inside each bag is worked out once up front.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bags {
+  names: Vec<String>,
+  ids: HashMap<String, usize>,
+  /// The colours each colour holds directly, and how many of each
+  contents: Vec<Vec<(usize, usize)>>,
+  /// The colours that hold each colour directly
+  holders: Vec<Vec<usize>>,
+  /// How many bags each bag holds altogether
+  totals: Vec<usize>,
 }
 
-#[derive(Debug)]
-struct Rule {
-  color: String,
-  contents: Vec<BagRule>,
-}
+impl Bags {
+  /// How many colours there are rules for
+  pub fn len(&self) -> usize {
+    self.names.len()
+  }
 
-fn parse_rules(i: &String) -> Vec<Rule> {
-  i.split("\n")
-    .map(|line| {
-      let re1 = Regex::new(r"^(.*) bags contain (.*).$").unwrap();
-      let re2 = Regex::new(r"(\d+) (.*) bag").unwrap();
-      re1.captures(line).map(|groups| {
-        let color: String = groups.get(1).unwrap().as_str().into();
-        let contents: Vec<BagRule> = groups
-          .get(2)
-          .unwrap()
-          .as_str()
-          .split(",")
-          .map(|rule_str| {
-            re2.captures(rule_str).map(|groups| {
-              let color: String = groups.get(2).unwrap().as_str().into();
-              let count: usize = groups.get(1).unwrap().as_str().parse().ok().unwrap();
-              BagRule { color, count }
-            })
-          })
-          .flatten()
+  pub fn is_empty(&self) -> bool {
+    self.names.is_empty()
+  }
+
+  /// The index of `color`, in the order the rules are given
+  pub fn id(&self, color: &str) -> Option<usize> {
+    self.ids.get(color).copied()
+  }
+
+  pub fn name(&self, id: usize) -> &str {
+    &self.names[id]
+  }
+
+  /// The colours a bag holds directly, with how many of each
+  pub fn contents(&self, id: usize) -> &[(usize, usize)] {
+    &self.contents[id]
+  }
+
+  /// The colours that hold a bag directly
+  pub fn holders(&self, id: usize) -> &[usize] {
+    &self.holders[id]
+  }
+
+  /// Every colour that eventually holds a bag, in no particular order
+  pub fn containers(&self, id: usize) -> Vec<usize> {
+    let mut seen = vec![false; self.len()];
+    let mut stack = vec![id];
+    let mut found = Vec::new();
+    while let Some(bag) = stack.pop() {
+      for &holder in &self.holders[bag] {
+        if !seen[holder] {
+          seen[holder] = true;
+          found.push(holder);
+          stack.push(holder);
+        }
+      }
+    }
+    found
+  }
+
+  /// How many bags a bag holds, counting bags inside bags
+  pub fn count_inside(&self, id: usize) -> usize {
+    self.totals[id]
+  }
+
+  /// The rules as a Graphviz DOT digraph, with each edge labelled with how
+  /// many bags are held.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use advent_of_code_2020::day::day07::*;
+  ///
+  /// let bags = parse("bright white bags contain 1 shiny gold bag.
+  ///shiny gold bags contain 2 faded blue bags.
+  ///faded blue bags contain no other bags.").unwrap();
+  /// assert_eq!(
+  ///   bags.to_dot(),
+  ///   r#"digraph bags {
+  ///   "bright white";
+  ///   "shiny gold";
+  ///   "faded blue";
+  ///   "bright white" -> "shiny gold" [label=1];
+  ///   "shiny gold" -> "faded blue" [label=2];
+  /// }
+  /// "#
+  /// );
+  /// ```
+  pub fn to_dot(&self) -> String {
+    let quote = |name: &str| format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""));
+    let mut out = String::from("digraph bags {\n");
+    for name in &self.names {
+      out.push_str(&format!("  {};\n", quote(name)));
+    }
+    for (bag, contents) in self.contents.iter().enumerate() {
+      for &(inner, count) in contents {
+        out.push_str(&format!(
+          "  {} -> {} [label={}];\n",
+          quote(&self.names[bag]),
+          quote(&self.names[inner]),
+          count
+        ));
+      }
+    }
+    out.push_str("}\n");
+    out
+  }
+
+  /// A colour that holds itself, by way of the colours after it, if any
+  fn find_cycle(&self) -> Option<Vec<usize>> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum State {
+      New,
+      OnPath,
+      Done,
+    }
+    let mut state = vec![State::New; self.len()];
+    for start in 0..self.len() {
+      if state[start] != State::New {
+        continue;
+      }
+      let mut path = vec![(start, 0)];
+      state[start] = State::OnPath;
+      while let Some(&mut (bag, ref mut next)) = path.last_mut() {
+        let inner = match self.contents[bag].get(*next) {
+          Some(&(inner, _)) => inner,
+          None => {
+            state[bag] = State::Done;
+            path.pop();
+            continue;
+          }
+        };
+        *next += 1;
+        match state[inner] {
+          State::New => {
+            state[inner] = State::OnPath;
+            path.push((inner, 0));
+          }
+          State::OnPath => {
+            let from = path.iter().position(|&(b, _)| b == inner).unwrap();
+            return Some(path[from..].iter().map(|&(b, _)| b).collect());
+          }
+          State::Done => {}
+        }
+      }
+    }
+    None
+  }
+
+  /// Fill in `totals`, innermost bags first. Returns the colour whose total
+  /// is too large to count if there is one.
+  fn count_totals(&mut self) -> std::result::Result<(), usize> {
+    let mut done = vec![false; self.len()];
+    for start in 0..self.len() {
+      let mut stack = vec![start];
+      while let Some(&bag) = stack.last() {
+        if done[bag] {
+          stack.pop();
+          continue;
+        }
+        let pending: Vec<usize> = self.contents[bag]
+          .iter()
+          .map(|&(inner, _)| inner)
+          .filter(|&inner| !done[inner])
           .collect();
-        Rule { color, contents }
-      })
-    })
-    .flatten()
-    .collect()
+        if !pending.is_empty() {
+          stack.extend(pending);
+          continue;
+        }
+        self.totals[bag] = self.contents[bag]
+          .iter()
+          .try_fold(0usize, |total, &(inner, count)| {
+            let each = self.totals[inner].checked_add(1)?;
+            total.checked_add(count.checked_mul(each)?)
+          })
+          .ok_or(bag)?;
+        done[bag] = true;
+        stack.pop();
+      }
+    }
+    Ok(())
+  }
 }
 
-fn get_possible_contents(color: &String, rules: &Vec<Rule>, results: &mut HashSet<String>) {
-  let rule = rules.iter().find(|r| r.color == *color).unwrap();
-  rule.contents.iter().for_each(|bag_rule| {
-    results.insert(bag_rule.color.to_string());
-    get_possible_contents(&bag_rule.color, rules, results);
-  });
-}
+/// Parse one `<color> bags contain <count> <color> bags, ...` rule per line.
+/// Every colour needs exactly one rule, and no bag may end up inside itself.
+pub fn parse(i: &str) -> Result<Bags> {
+  let mut rules = Vec::new();
+  for line in i.lines().filter(|line| !line.is_empty()) {
+    let (color, raw_contents) = line
+      .strip_suffix('.')
+      .and_then(|rule| rule.split_once(" bags contain "))
+      .ok_or_else(|| Error::at(i, line, "expected `<color> bags contain <contents>.`"))?;
+    let contents = match raw_contents {
+      "no other bags" => Vec::new(),
+      raw_contents => raw_contents
+        .split(", ")
+        .map(|rule_str| {
+          let (count, inner) = rule_str
+            .split_once(' ')
+            .and_then(|(count, rest)| {
+              let inner = rest
+                .strip_suffix(" bags")
+                .or_else(|| rest.strip_suffix(" bag"))?;
+              Some((count, inner))
+            })
+            .filter(|(count, _)| count.chars().all(|c| c.is_ascii_digit()))
+            .ok_or_else(|| Error::at(i, rule_str, "expected `<count> <color> bags`"))?;
+          let count: usize = count
+            .parse()
+            .map_err(|_| Error::at(i, rule_str, "count is too large"))?;
+          Ok((count, inner))
+        })
+        .collect::<Result<Vec<_>>>()?,
+    };
+    rules.push((line, color, contents));
+  }
 
-fn count_can_contain(color: String, rules: &Vec<Rule>) -> usize {
-  rules
-    .iter()
-    .map(|rule| {
-      let mut contents = HashSet::new();
-      get_possible_contents(&rule.color, rules, &mut contents);
-      if contents.contains(&color) {
-        Some(&color)
-      } else {
-        None
-      }
-    })
-    .filter(|c| c.is_some())
-    .count()
+  let mut ids = HashMap::new();
+  for (idx, &(line, color, _)) in rules.iter().enumerate() {
+    if ids.insert(color.to_string(), idx).is_some() {
+      return Err(Error::at(i, line, "a second rule for this colour"));
+    }
+  }
+  let mut contents = vec![Vec::new(); rules.len()];
+  let mut holders = vec![Vec::new(); rules.len()];
+  for (bag, (_, _, rule)) in rules.iter().enumerate() {
+    for &(count, inner) in rule {
+      let inner_id = *ids
+        .get(inner)
+        .ok_or_else(|| Error::at(i, inner, "no rule for this colour"))?;
+      contents[bag].push((inner_id, count));
+      holders[inner_id].push(bag);
+    }
+  }
+
+  let mut bags = Bags {
+    names: rules
+      .iter()
+      .map(|&(_, color, _)| color.to_string())
+      .collect(),
+    ids,
+    contents,
+    holders,
+    totals: vec![0; rules.len()],
+  };
+  if let Some(cycle) = bags.find_cycle() {
+    let names: Vec<&str> = cycle
+      .iter()
+      .chain(&cycle[..1])
+      .map(|&b| bags.name(b))
+      .collect();
+    return Err(Error::at(
+      i,
+      rules[cycle[0]].0,
+      format!("bags end up inside themselves: {}", names.join(" -> ")),
+    ));
+  }
+  bags
+    .count_totals()
+    .map_err(|bag| Error::at(i, rules[bag].0, "too many bags inside to count"))?;
+  Ok(bags)
 }
 
 /// How many bags must your shiny gold bag contain
@@ -97,7 +329,7 @@ fn count_can_contain(color: String, rules: &Vec<Rule>) -> usize {
 /// ```
 /// use advent_of_code_2020::day::day07::*;
 ///
-/// let input = "light red bags contain 1 bright white bag, 2 muted yellow bags.
+/// let input = parse("light red bags contain 1 bright white bag, 2 muted yellow bags.
 ///dark orange bags contain 3 bright white bags, 4 muted yellow bags.
 ///bright white bags contain 1 shiny gold bag.
 ///muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.
@@ -105,25 +337,128 @@ fn count_can_contain(color: String, rules: &Vec<Rule>) -> usize {
 ///dark olive bags contain 3 faded blue bags, 4 dotted black bags.
 ///vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.
 ///faded blue bags contain no other bags.
-///dotted black bags contain no other bags.".to_string();
+///dotted black bags contain no other bags.").unwrap();
 /// let result = part2(&input);
 /// assert_eq!(result, 32);
 /// ```
-pub fn part2(i: &String) -> usize {
-  let rules = parse_rules(i);
-  count_contents("shiny gold".into(), &rules)
+pub fn part2(bags: &Bags) -> usize {
+  bags_inside(bags, "shiny gold").expect("there is a rule for shiny gold bags")
+}
+
+/// Every colour that can eventually hold a `color` bag, sorted, or `None`
+/// if there's no rule for `color`
+pub fn containers_of<'a>(bags: &'a Bags, color: &str) -> Option<Vec<&'a str>> {
+  let id = bags.id(color)?;
+  let mut names: Vec<&str> = bags
+    .containers(id)
+    .into_iter()
+    .map(|bag| bags.name(bag))
+    .collect();
+  names.sort_unstable();
+  Some(names)
+}
+
+/// How many bags a `color` bag must hold, or `None` if there's no rule for
+/// `color`
+pub fn bags_inside(bags: &Bags, color: &str) -> Option<usize> {
+  bags.id(color).map(|id| bags.count_inside(id))
 }
 
-fn count_contents(color: String, rules: &Vec<Rule>) -> usize {
-  let mut count: usize = 0;
-  get_contents_count(&color, rules, &mut count, 1);
-  count
+/// A bag and everything inside it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Tree<'a> {
+  pub color: &'a str,
+  /// How many of these bags the bag around it holds
+  pub count: usize,
+  /// How many of these bags there are here altogether, counting the bags
+  /// around it
+  pub total: usize,
+  pub contents: Vec<Tree<'a>>,
 }
 
-fn get_contents_count(color: &String, rules: &Vec<Rule>, count: &mut usize, multiplier: usize) {
-  let rule = rules.iter().find(|r| r.color == *color).unwrap();
-  rule.contents.iter().for_each(|bag_rule| {
-    *count = *count + (bag_rule.count * multiplier);
-    get_contents_count(&bag_rule.color, rules, count, multiplier * bag_rule.count);
-  });
+impl Tree<'_> {
+  fn write(&self, f: &mut fmt::Formatter, depth: usize) -> fmt::Result {
+    let indent = depth * 2;
+    if depth == 0 {
+      writeln!(f, "{}", self.color)?;
+    } else if self.total == self.count {
+      writeln!(
+        f,
+        "{:indent$}{} {}",
+        "",
+        self.count,
+        self.color,
+        indent = indent
+      )?;
+    } else {
+      writeln!(
+        f,
+        "{:indent$}{} {} ({} in all)",
+        "",
+        self.count,
+        self.color,
+        self.total,
+        indent = indent
+      )?;
+    }
+    self
+      .contents
+      .iter()
+      .try_for_each(|inner| inner.write(f, depth + 1))
+  }
+}
+
+/// One line per bag, indented inside the bag holding it
+impl fmt::Display for Tree<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    self.write(f, 0)
+  }
+}
+
+/// Everything inside a `color` bag, bag by bag, or `None` if there's no
+/// rule for `color`
+///
+/// # Example
+///
+/// ```
+/// use advent_of_code_2020::day::day07::*;
+///
+/// let bags = parse("shiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.
+///dark olive bags contain 3 faded blue bags, 4 dotted black bags.
+///vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.
+///faded blue bags contain no other bags.
+///dotted black bags contain no other bags.").unwrap();
+/// let tree = contents_tree(&bags, "shiny gold").unwrap();
+/// assert_eq!(
+///   tree.to_string(),
+///   "shiny gold
+///   1 dark olive
+///     3 faded blue
+///     4 dotted black
+///   2 vibrant plum
+///     5 faded blue (10 in all)
+///     6 dotted black (12 in all)
+/// "
+/// );
+/// assert_eq!(bags_inside(&bags, "vibrant plum"), Some(11));
+/// assert_eq!(
+///   containers_of(&bags, "faded blue").unwrap(),
+///   vec!["dark olive", "shiny gold", "vibrant plum"]
+/// );
+/// assert_eq!(contents_tree(&bags, "muted yellow"), None);
+/// ```
+pub fn contents_tree<'a>(bags: &'a Bags, color: &str) -> Option<Tree<'a>> {
+  fn build(bags: &Bags, id: usize, count: usize, total: usize) -> Tree<'_> {
+    Tree {
+      color: bags.name(id),
+      count,
+      total,
+      contents: bags
+        .contents(id)
+        .iter()
+        .map(|&(inner, n)| build(bags, inner, n, total * n))
+        .collect(),
+    }
+  }
+  bags.id(color).map(|id| build(bags, id, 1, 1))
 }
@@ -1,4 +1,46 @@
-use std::collections::HashSet;
+use crate::console::{self, Exit, Vm};
+use crate::{Error, Result, Solution};
+
+pub use crate::console::Command;
+
+pub struct Day08;
+
+impl Solution for Day08 {
+  type Input = Vec<Command>;
+  type Part1 = i32;
+  type Part2 = i32;
+
+  fn parse(input: &str) -> Result<Vec<Command>> {
+    let commands = parse(input)?;
+    let first = input.trim_start().lines().next().unwrap_or("");
+    if commands.is_empty() {
+      return Err(Error::at(input, first, "expected at least one instruction"));
+    }
+    if Vm::new(&commands).run() == Exit::Terminated {
+      return Err(Error::at(
+        input,
+        first,
+        "program already completes without a swap",
+      ));
+    }
+    if console::repair(&commands).is_none() {
+      return Err(Error::at(
+        input,
+        first,
+        "no single swap lets the program complete",
+      ));
+    }
+    Ok(commands)
+  }
+
+  fn part1(input: &Vec<Command>) -> i32 {
+    part1(input)
+  }
+
+  fn part2(input: &Vec<Command>) -> i32 {
+    part2(input)
+  }
+}
 
 /// Each line is a command. `acc` increments the global accumulator,
 /// `jmp` moves relative to the current command, and `nop` is no operation.
@@ -10,7 +52,7 @@ use std::collections::HashSet;
 /// ```
 /// use advent_of_code_2020::day::day08::*;
 ///
-/// let input = "nop +0
+/// let input = parse("nop +0
 ///acc +1
 ///jmp +4
 ///acc +3
@@ -18,73 +60,19 @@ use std::collections::HashSet;
 ///acc -99
 ///acc +1
 ///jmp -4
-///acc +6".to_string();
+///acc +6").unwrap();
 /// let result = part1(&input);
 /// assert_eq!(result, 5);
 /// ```
-pub fn part1(i: &String) -> i32 {
-  let commands = parse(i);
-  let mut acc = 0;
-  let mut index = 0;
-  let mut call_stack: HashSet<i32> = HashSet::new();
-  run_until_loop(&mut index, &mut acc, &mut call_stack, &commands);
-  acc
-}
-
-#[derive(Debug, Clone)]
-enum Command {
-  Noop(i32),
-  Jump(i32),
-  Add(i32),
+pub fn part1(commands: &[Command]) -> i32 {
+  let mut vm = Vm::new(commands);
+  vm.run();
+  vm.acc()
 }
 
-fn parse(i: &String) -> Vec<Command> {
-  i.split("\n")
-    .map(|line| {
-      let parts1: Vec<&str> = line.split(" ").collect();
-      let raw_command = parts1[0];
-      let arg: i32 = (match parts1[1].strip_prefix("+") {
-        Some(raw_arg) => raw_arg,
-        None => parts1[1],
-      })
-      .parse()
-      .ok()
-      .unwrap();
-      (match raw_command {
-        "nop" => Some(Command::Noop(arg)),
-        "jmp" => Some(Command::Jump(arg)),
-        "acc" => Some(Command::Add(arg)),
-        _ => None,
-      })
-      .expect("unmatched command")
-    })
-    .collect()
-}
-
-fn process_command(index: i32, acc: i32, commands: &Vec<Command>) -> (i32, i32) {
-  println!("executing command {:?}", commands.get(index as usize));
-  (match commands.get(index as usize) {
-    Some(Command::Noop(_)) => Some((index + 1, acc)),
-    Some(Command::Jump(delta)) => Some((index + delta, acc)),
-    Some(Command::Add(delta)) => Some((index + 1, acc + delta)),
-    _ => None,
-  })
-  .expect("unable to process command")
-}
-
-fn run_until_loop(
-  index: &mut i32,
-  acc: &mut i32,
-  call_stack: &mut HashSet<i32>,
-  commands: &Vec<Command>,
-) {
-  call_stack.insert(*index);
-  let (new_index, new_acc) = process_command(*index, *acc, commands);
-  *index = new_index;
-  *acc = new_acc;
-  if !call_stack.contains(&new_index) {
-    run_until_loop(index, acc, call_stack, commands);
-  }
+/// Parse one `<op> <signed argument>` command per line
+pub fn parse(i: &str) -> Result<Vec<Command>> {
+  console::parse(i)
 }
 
 /// Change one jmp to nop or nop to jmp and get the program to complete.
@@ -95,7 +83,7 @@ fn run_until_loop(
 /// ```
 /// use advent_of_code_2020::day::day08::*;
 ///
-/// let input = "nop +0
+/// let input = parse("nop +0
 ///acc +1
 ///jmp +4
 ///acc +3
@@ -103,56 +91,15 @@ fn run_until_loop(
 ///acc -99
 ///acc +1
 ///jmp -4
-///acc +6".to_string();
+///acc +6").unwrap();
 /// let result = part2(&input);
 /// assert_eq!(result, 8);
 /// ```
-pub fn part2(i: &String) -> i32 {
-  let commands = parse(i);
-  commands
-    .iter()
-    .enumerate()
-    .filter_map(|(idx, cmd)| match cmd {
-      Command::Jump(_) => Some(idx),
-      Command::Noop(_) => Some(idx),
-      _ => None,
-    })
-    .find_map(|index| attempt_swap(index, &commands))
-    .expect("swapped them all and nothing completed")
-}
-
-fn attempt_swap(swap_index: usize, commands: &Vec<Command>) -> Option<i32> {
-  let new_command = match commands.get(swap_index) {
-    Some(Command::Jump(arg)) => Command::Noop(*arg),
-    Some(Command::Noop(arg)) => Command::Jump(*arg),
-    _ => Command::Noop(0),
-  };
-  let mut modified_commands = commands.clone();
-  modified_commands.splice(
-    swap_index..swap_index + 1,
-    vec![new_command].iter().cloned(),
-  );
-  let mut acc = 0;
-  let mut index = 0;
-  let mut call_stack: HashSet<i32> = HashSet::new();
-  run_until_complete(&mut index, &mut acc, &mut call_stack, &modified_commands)
-}
-
-fn run_until_complete(
-  index: &mut i32,
-  acc: &mut i32,
-  call_stack: &mut HashSet<i32>,
-  commands: &Vec<Command>,
-) -> Option<i32> {
-  call_stack.insert(*index);
-  let (new_index, new_acc) = process_command(*index, *acc, commands);
-  *index = new_index;
-  *acc = new_acc;
-  if new_index == commands.len() as i32 {
-    return Some(new_acc);
-  }
-  if call_stack.contains(&new_index) {
-    return None;
-  }
-  run_until_complete(index, acc, call_stack, commands)
+pub fn part2(commands: &[Command]) -> i32 {
+  let index = console::repair(commands).expect("no single swap lets the program complete");
+  let mut repaired = commands.to_vec();
+  repaired[index] = repaired[index].flipped();
+  let mut vm = Vm::new(&repaired);
+  vm.run();
+  vm.acc()
 }
@@ -1,3 +1,35 @@
+use crate::{Error, Result, Solution};
+
+pub struct Day09;
+
+const PREAMBLE_LENGTH: usize = 25;
+
+impl Solution for Day09 {
+  type Input = Vec<usize>;
+  type Part1 = usize;
+  type Part2 = usize;
+
+  fn parse(input: &str) -> Result<Vec<usize>> {
+    let numbers = parse(input)?;
+    if numbers.len() <= PREAMBLE_LENGTH {
+      return Err(Error::at(
+        input,
+        &input[input.len()..],
+        format!("expected more than the {} number preamble", PREAMBLE_LENGTH),
+      ));
+    }
+    Ok(numbers)
+  }
+
+  fn part1(input: &Vec<usize>) -> usize {
+    part1(input, PREAMBLE_LENGTH)
+  }
+
+  fn part2(input: &Vec<usize>) -> usize {
+    part2(input, PREAMBLE_LENGTH)
+  }
+}
+
 /// Each line after the preamble (25 lines in input data, 5 in the example below)
 /// each line must be a sum of 2 of the preceeding preamble length numbers.
 /// Find the first number that breaks that rule.
@@ -7,7 +39,7 @@
 /// ```
 /// use advent_of_code_2020::day::day09::*;
 ///
-/// let input = "35
+/// let input = parse("35
 ///20
 ///15
 ///25
@@ -26,22 +58,28 @@
 ///299
 ///277
 ///309
-///576".to_string();
+///576").unwrap();
 /// let result = part1(&input, 5);
 /// assert_eq!(result, 127);
 /// ```
-pub fn part1(i: &String, preamble_length: usize) -> usize {
-  let numbers = parse(i);
-  find_first_exception(preamble_length, &numbers)
+pub fn part1(numbers: &[usize], preamble_length: usize) -> usize {
+  find_first_exception(preamble_length, numbers)
 }
 
-fn parse(i: &String) -> Vec<usize> {
-  i.split("\n")
-    .filter_map(|s| s.trim().parse().ok())
+/// Parse one number per line
+pub fn parse(i: &str) -> Result<Vec<usize>> {
+  i.lines()
+    .filter(|line| !line.trim().is_empty())
+    .map(|line| {
+      line
+        .trim()
+        .parse()
+        .map_err(|_| Error::at(i, line, "expected a number"))
+    })
     .collect()
 }
 
-fn find_first_exception(preamble_length: usize, numbers: &Vec<usize>) -> usize {
+fn find_first_exception(preamble_length: usize, numbers: &[usize]) -> usize {
   let enumerated: Vec<(usize, usize)> = numbers.iter().cloned().enumerate().collect();
   enumerated[preamble_length..]
     .iter()
@@ -69,7 +107,7 @@ fn find_first_exception(preamble_length: usize, numbers: &Vec<usize>) -> usize {
 /// ```
 /// use advent_of_code_2020::day::day09::*;
 ///
-/// let input = "35
+/// let input = parse("35
 ///20
 ///15
 ///25
@@ -88,17 +126,16 @@ fn find_first_exception(preamble_length: usize, numbers: &Vec<usize>) -> usize {
 ///299
 ///277
 ///309
-///576".to_string();
+///576").unwrap();
 /// let result = part2(&input, 5);
 /// assert_eq!(result, 62);
 /// ```
-pub fn part2(i: &String, preamble_length: usize) -> usize {
-  let numbers = parse(i);
-  let exception = find_first_exception(preamble_length, &numbers);
-  find_contiguous_sum_set(exception, &numbers)
+pub fn part2(numbers: &[usize], preamble_length: usize) -> usize {
+  let exception = find_first_exception(preamble_length, numbers);
+  find_contiguous_sum_set(exception, numbers)
 }
 
-fn find_contiguous_sum_set(sum: usize, numbers: &Vec<usize>) -> usize {
+fn find_contiguous_sum_set(sum: usize, numbers: &[usize]) -> usize {
   let enumerated: Vec<(usize, usize)> = numbers.iter().cloned().enumerate().collect();
   let (start_idx, end_idx) = enumerated
     .iter()
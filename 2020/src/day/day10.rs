@@ -1,5 +1,45 @@
 use std::collections::HashMap;
 
+use crate::{Error, Result, Solution};
+
+pub struct Day10;
+
+impl Solution for Day10 {
+  type Input = Vec<i64>;
+  type Part1 = i64;
+  type Part2 = usize;
+
+  fn parse(input: &str) -> Result<Vec<i64>> {
+    parse(input)
+  }
+
+  fn part1(input: &Vec<i64>) -> i64 {
+    part1(input)
+  }
+
+  fn part2(input: &Vec<i64>) -> usize {
+    part2(input)
+  }
+}
+
+/// Parse one adapter joltage per line. There must be at least one adapter.
+pub fn parse(i: &str) -> Result<Vec<i64>> {
+  let joltages: Vec<i64> = i
+    .lines()
+    .filter(|line| !line.trim().is_empty())
+    .map(|line| {
+      line
+        .trim()
+        .parse()
+        .map_err(|_| Error::at(i, line, "expected a number"))
+    })
+    .collect::<Result<_>>()?;
+  if joltages.is_empty() {
+    return Err(Error::at(i, &i[i.len()..], "expected at least one adapter"));
+  }
+  Ok(joltages)
+}
+
 /// Find the # of 1 diff * # of 3 diff
 ///
 /// # Example
@@ -7,7 +47,7 @@ use std::collections::HashMap;
 /// ```
 /// use advent_of_code_2020::day::day10::*;
 ///
-/// let input = "16
+/// let input = parse("16
 ///10
 ///15
 ///5
@@ -17,13 +57,13 @@ use std::collections::HashMap;
 ///19
 ///6
 ///12
-///4".to_string();
+///4").unwrap();
 /// let result = part1(&input);
 /// assert_eq!(result, 35);
 /// ```
-pub fn part1(i: &String) -> i32 {
-  let mut adapter_joltages: Vec<i32> = i.split("\n").filter_map(|s| s.parse().ok()).collect();
-  adapter_joltages.sort();
+pub fn part1(i: &[i64]) -> i64 {
+  let mut adapter_joltages = i.to_vec();
+  adapter_joltages.sort_unstable();
   adapter_joltages.insert(0, 0);
   let (count_1, count_3) =
     adapter_joltages
@@ -51,7 +91,7 @@ pub fn part1(i: &String) -> i32 {
 /// ```
 /// use advent_of_code_2020::day::day10::*;
 ///
-/// let input = "16
+/// let input = parse("16
 ///10
 ///15
 ///5
@@ -61,19 +101,19 @@ pub fn part1(i: &String) -> i32 {
 ///19
 ///6
 ///12
-///4".to_string();
+///4").unwrap();
 /// let result = part2(&input);
 /// assert_eq!(result, 8);
 /// ```
-pub fn part2(i: &String) -> usize {
-  let mut adapter_joltages: Vec<isize> = i.split("\n").filter_map(|s| s.parse().ok()).collect();
-  adapter_joltages.sort();
+pub fn part2(i: &[i64]) -> usize {
+  let mut adapter_joltages = i.to_vec();
+  adapter_joltages.sort_unstable();
   let mut jumps_per_adapter = HashMap::new();
   jumps_per_adapter.insert(0, 1);
   for &joltage in &adapter_joltages {
     let mut jumps = 0;
     for previous in 0.max(joltage - 3)..joltage {
-      jumps = jumps + jumps_per_adapter.get(&previous).unwrap_or(&0);
+      jumps += jumps_per_adapter.get(&previous).unwrap_or(&0);
     }
     jumps_per_adapter.insert(joltage, jumps);
   }
@@ -1,3 +1,25 @@
+use crate::{Error, Result, Solution};
+
+pub struct Day12;
+
+impl Solution for Day12 {
+  type Input = Vec<Command>;
+  type Part1 = i32;
+  type Part2 = i32;
+
+  fn parse(input: &str) -> Result<Vec<Command>> {
+    parse(input)
+  }
+
+  fn part1(input: &Vec<Command>) -> i32 {
+    part1(input)
+  }
+
+  fn part2(input: &Vec<Command>) -> i32 {
+    part2(input)
+  }
+}
+
 /// Each line is a navigational command. NSEW mean move in that direction that many units.
 /// F means move in the facing direction
 /// L and R rotate left & right  by degrees.
@@ -8,23 +30,21 @@
 /// ```
 /// use advent_of_code_2020::day::day12::*;
 ///
-/// let input = "F10
+/// let input = parse("F10
 ///N3
 ///F7
 ///R90
-///F11".to_string();
+///F11").unwrap();
 /// let result = part1(&input);
 /// assert_eq!(result, 25);
 /// ```
-pub fn part1(i: &String) -> i32 {
-  let commands = parse1(i);
-  let ship_state = process_commands1(&commands);
-  println!("final coordinates: {:?}", ship_state.coordinates);
+pub fn part1(commands: &[Command]) -> i32 {
+  let ship_state = process_commands1(commands);
   ship_state.coordinates.x.abs() + ship_state.coordinates.y.abs()
 }
 
 #[derive(Clone, Copy, Debug)]
-enum Command {
+pub enum Command {
   North(i32),
   South(i32),
   East(i32),
@@ -45,7 +65,7 @@ impl Vector2 {
     Self { x, y }
   }
 
-  fn to_degrees(&self) -> i32 {
+  fn to_degrees(self) -> i32 {
     match (self.x, self.y) {
       (1, 0) => 0,
       (0, -1) => 90,
@@ -96,33 +116,14 @@ impl ShipState1 {
   }
 }
 
-fn parse1(i: &String) -> Vec<Command> {
-  i.split("\n")
-    .filter_map(|line| {
-      let first_letter = line.chars().next();
-      let rest = line[1..].parse::<i32>().ok();
-      match (first_letter, rest) {
-        (Some('N'), Some(i)) => Some(Command::North(i)),
-        (Some('S'), Some(i)) => Some(Command::South(i)),
-        (Some('E'), Some(i)) => Some(Command::East(i)),
-        (Some('W'), Some(i)) => Some(Command::West(i)),
-        (Some('F'), Some(i)) => Some(Command::Forward(i)),
-        (Some('R'), Some(i)) => Some(Command::RotateRight(i)),
-        (Some('L'), Some(i)) => Some(Command::RotateLeft(i)),
-        _ => panic!("unhandled command: {}", line),
-      }
-    })
-    .collect()
-}
-
-fn process_commands1(commands: &Vec<Command>) -> ShipState1 {
+fn process_commands1(commands: &[Command]) -> ShipState1 {
   commands
     .iter()
     .fold(ShipState1::new(), |ship_state, command| match command {
       Command::North(i) => ship_state.move_by(0, *i),
-      Command::South(i) => ship_state.move_by(0, -1 * i),
+      Command::South(i) => ship_state.move_by(0, -i),
       Command::East(i) => ship_state.move_by(*i, 0),
-      Command::West(i) => ship_state.move_by(-1 * i, 0),
+      Command::West(i) => ship_state.move_by(-i, 0),
       Command::RotateRight(deg) => ship_state.rotate(*deg),
       Command::RotateLeft(deg) => ship_state.rotate(360 - deg),
       Command::Forward(i) => {
@@ -141,18 +142,16 @@ fn process_commands1(commands: &Vec<Command>) -> ShipState1 {
 /// ```
 /// use advent_of_code_2020::day::day12::*;
 ///
-/// let input = "F10
+/// let input = parse("F10
 ///N3
 ///F7
 ///R90
-///F11".to_string();
+///F11").unwrap();
 /// let result = part2(&input);
 /// assert_eq!(result, 286);
 /// ```
-pub fn part2(i: &String) -> i32 {
-  let commands = parse(i);
-  let ship_state = process_commands(&commands);
-  println!("final coordinates: {:?}", ship_state.coordinates);
+pub fn part2(commands: &[Command]) -> i32 {
+  let ship_state = process_commands(commands);
   ship_state.coordinates.x.abs() + ship_state.coordinates.y.abs()
 }
 
@@ -206,33 +205,46 @@ impl ShipState {
   }
 }
 
-fn parse(i: &String) -> Vec<Command> {
-  i.split("\n")
-    .filter_map(|line| {
-      let first_letter = line.chars().next();
-      let rest = line[1..].parse::<i32>().ok();
-      match (first_letter, rest) {
-        (Some('N'), Some(i)) => Some(Command::North(i)),
-        (Some('S'), Some(i)) => Some(Command::South(i)),
-        (Some('E'), Some(i)) => Some(Command::East(i)),
-        (Some('W'), Some(i)) => Some(Command::West(i)),
-        (Some('F'), Some(i)) => Some(Command::Forward(i)),
-        (Some('R'), Some(i)) => Some(Command::RotateRight(i)),
-        (Some('L'), Some(i)) => Some(Command::RotateLeft(i)),
-        _ => panic!("unhandled command: {}", line),
+/// Parse one navigation instruction per line, e.g. `F10` or `R90`
+pub fn parse(i: &str) -> Result<Vec<Command>> {
+  i.lines()
+    .filter(|line| !line.is_empty())
+    .map(|line| {
+      let action = line.chars().next().unwrap_or_default();
+      let raw_value = &line[action.len_utf8()..];
+      let value: i32 = raw_value
+        .parse()
+        .map_err(|_| Error::at(i, raw_value, "expected a number"))?;
+      let rotation = || match value {
+        90 | 180 | 270 => Ok(value),
+        _ => Err(Error::at(
+          i,
+          raw_value,
+          "expected a rotation of 90, 180 or 270",
+        )),
+      };
+      match action {
+        'N' => Ok(Command::North(value)),
+        'S' => Ok(Command::South(value)),
+        'E' => Ok(Command::East(value)),
+        'W' => Ok(Command::West(value)),
+        'F' => Ok(Command::Forward(value)),
+        'R' => rotation().map(Command::RotateRight),
+        'L' => rotation().map(Command::RotateLeft),
+        _ => Err(Error::at(i, line, "expected one of `NSEWFRL`")),
       }
     })
     .collect()
 }
 
-fn process_commands(commands: &Vec<Command>) -> ShipState {
+fn process_commands(commands: &[Command]) -> ShipState {
   commands
     .iter()
     .fold(ShipState::new(), |ship_state, command| match command {
       Command::North(i) => ship_state.move_waypoint(0, *i),
-      Command::South(i) => ship_state.move_waypoint(0, -1 * i),
+      Command::South(i) => ship_state.move_waypoint(0, -i),
       Command::East(i) => ship_state.move_waypoint(*i, 0),
-      Command::West(i) => ship_state.move_waypoint(-1 * i, 0),
+      Command::West(i) => ship_state.move_waypoint(-i, 0),
       Command::RotateRight(deg) => ship_state.rotate_waypoint(*deg),
       Command::RotateLeft(deg) => ship_state.rotate_waypoint(360 - deg),
       Command::Forward(times) => ship_state.move_to_waypoint(*times),
@@ -1,3 +1,26 @@
+use crate::math;
+use crate::{Error, Result, Solution};
+
+pub struct Day13;
+
+impl Solution for Day13 {
+  type Input = Notes;
+  type Part1 = i64;
+  type Part2 = i128;
+
+  fn parse(input: &str) -> Result<Notes> {
+    parse(input)
+  }
+
+  fn part1(input: &Notes) -> i64 {
+    part1(input)
+  }
+
+  fn part2(input: &Notes) -> i128 {
+    part2(input)
+  }
+}
+
 /// What is the ID of the earliest bus you can take to the airport
 /// multiplied by the number of minutes you'll need to wait for that bus?
 ///
@@ -9,36 +32,90 @@
 /// ```
 /// use advent_of_code_2020::day::day13::*;
 ///
-/// let input = "939
-///7,13,x,x,59,x,31,19".to_string();
+/// let input = parse("939
+///7,13,x,x,59,x,31,19").unwrap();
 /// let result = part1(&input);
 /// assert_eq!(result, 295);
 /// ```
-pub fn part1(i: &String) -> i32 {
-  let (arrival_timestamp, bus_ids) = parse(&i);
-  let (departure_time, bus_id) = find_earliest_departing_bus_id(&arrival_timestamp, &bus_ids);
-  (departure_time - arrival_timestamp) * bus_id
+pub fn part1(notes: &Notes) -> i64 {
+  let bus_ids: Vec<i64> = notes.buses.iter().map(|&(_, id)| id).collect();
+  let (departure_time, bus_id) = find_earliest_departing_bus_id(notes.arrival_timestamp, &bus_ids);
+  (departure_time - notes.arrival_timestamp) * bus_id
 }
 
-fn parse(i: &String) -> (i32, Vec<i32>) {
-  let mut parts = i.split("\n");
-  let arrival_timestamp: i32 = parts.next().unwrap().parse().ok().unwrap();
-  let bus_ids: Vec<i32> = parts
+/// Your earliest departure time and each bus ID alongside its position in the
+/// schedule, with the earliest timestamp the schedule lines up at.
+#[derive(Clone, Debug)]
+pub struct Notes {
+  arrival_timestamp: i64,
+  buses: Vec<(i64, i64)>,
+  aligned_timestamp: i128,
+}
+
+/// Parse the arrival timestamp line followed by the comma separated schedule,
+/// where `x` marks an out of service bus. Some timestamp must line up every
+/// bus with its position, and the schedule must repeat within 128 bits.
+pub fn parse(i: &str) -> Result<Notes> {
+  let mut parts = i.lines();
+  let raw_timestamp = parts.next().unwrap_or(i);
+  let arrival_timestamp: i64 = raw_timestamp
+    .trim()
+    .parse()
+    .map_err(|_| Error::at(i, raw_timestamp, "expected an arrival timestamp"))?;
+  let raw_buses = parts
     .next()
-    .unwrap()
-    .split(",")
-    .filter_map(|s| s.parse().ok())
-    .collect();
-  (arrival_timestamp, bus_ids)
+    .ok_or_else(|| Error::at(i, &i[i.len()..], "expected a line of bus ids"))?;
+  let mut buses = Vec::new();
+  // Every aligned timestamp is `aligned` plus a multiple of `period`
+  let (mut aligned, mut period): (i128, i128) = (0, 1);
+  for (idx, s) in raw_buses.split(',').enumerate() {
+    if s == "x" {
+      continue;
+    }
+    let id: i64 = match s.parse() {
+      Ok(id) if id >= 1 => id,
+      Ok(_) => return Err(Error::at(i, s, "bus ids must be at least 1")),
+      Err(_) => return Err(Error::at(i, s, "expected a bus id or `x`")),
+    };
+    let offset = idx as i64;
+    // `crt` needs twice the new period to fit
+    let id_128 = i128::from(id);
+    if (period / math::gcd(period, id_128))
+      .checked_mul(id_128)
+      .and_then(|next| next.checked_mul(2))
+      .is_none()
+    {
+      return Err(Error::at(i, s, "schedule doesn't repeat within 128 bits"));
+    }
+    // Bus `id` leaving `offset` minutes after t means t ≡ -offset (mod id)
+    let congruences = [(aligned, period), (i128::from(-offset), id_128)];
+    let (next_aligned, next_period) = math::crt(&congruences)
+      .ok_or_else(|| Error::at(i, s, "bus can't line up with the ones before it"))?;
+    aligned = next_aligned;
+    period = next_period;
+    buses.push((offset, id));
+  }
+  if buses.is_empty() {
+    return Err(Error::at(
+      i,
+      raw_buses,
+      "expected at least one bus in service",
+    ));
+  }
+  Ok(Notes {
+    arrival_timestamp,
+    buses,
+    aligned_timestamp: aligned,
+  })
 }
 
-fn find_earliest_departing_bus_id(&start: &i32, bus_ids: &Vec<i32>) -> (i32, i32) {
-  let mut bus_id: Option<i32> = None;
+fn find_earliest_departing_bus_id(start: i64, bus_ids: &[i64]) -> (i64, i64) {
+  let mut bus_id: Option<i64> = None;
   let mut time = start;
   while bus_id.is_none() {
     bus_id = bus_ids.iter().cloned().find(|id| time % id == 0);
     if bus_id.is_none() {
-      time = time + 1;
+      time += 1;
     }
   }
   (time, bus_id.unwrap())
@@ -51,58 +128,11 @@ fn find_earliest_departing_bus_id(&start: &i32, bus_ids: &Vec<i32>) -> (i32, i32
 /// ```
 /// use advent_of_code_2020::day::day13::*;
 ///
-/// let input = "939
-///7,13,x,x,59,x,31,19".to_string();
+/// let input = parse("939
+///7,13,x,x,59,x,31,19").unwrap();
 /// let result = part2(&input);
 /// assert_eq!(result, 1068781);
 /// ```
-pub fn part2(i: &String) -> i64 {
-  let enumerated_bus_ids: Vec<(i64, i64)> = i
-    .split("\n")
-    .nth(1)
-    .unwrap()
-    .split(",")
-    .enumerate()
-    .filter_map(|(idx, s)| match s.parse::<i64>() {
-      Ok(i) => Some((idx as i64, i)),
-      _ => None,
-    })
-    .collect();
-  let mods = enumerated_bus_ids
-    .iter()
-    .map(|&(_, b)| b)
-    .collect::<Vec<_>>();
-  let res = enumerated_bus_ids
-    .iter()
-    .map(|&(i, b)| (b - i))
-    .collect::<Vec<_>>();
-  chinese_remainder(&res, &mods).unwrap()
-}
-
-fn egcd(a: i64, b: i64) -> (i64, i64, i64) {
-  if a == 0 {
-    (b, 0, 1)
-  } else {
-    let (g, x, y) = egcd(b % a, a);
-    (g, y - (b / a) * x, x)
-  }
-}
-
-fn mod_inv(x: i64, n: i64) -> Option<i64> {
-  let (g, x, _) = egcd(x, n);
-  if g == 1 {
-    Some((x % n + n) % n)
-  } else {
-    None
-  }
-}
-
-fn chinese_remainder(residues: &[i64], modulii: &[i64]) -> Option<i64> {
-  let prod = modulii.iter().product::<i64>();
-  let mut sum = 0;
-  for (&residue, &modulus) in residues.iter().zip(modulii) {
-    let p = prod / modulus;
-    sum += residue * mod_inv(p, modulus)? * p
-  }
-  Some(sum % prod)
+pub fn part2(notes: &Notes) -> i128 {
+  notes.aligned_timestamp
 }
@@ -1,6 +1,28 @@
 use regex::Regex;
 use std::collections::HashMap;
 
+use crate::{Error, Result, Solution};
+
+pub struct Day14;
+
+impl Solution for Day14 {
+  type Input = Vec<Command>;
+  type Part1 = i64;
+  type Part2 = i64;
+
+  fn parse(input: &str) -> Result<Vec<Command>> {
+    parse(input)
+  }
+
+  fn part1(input: &Vec<Command>) -> i64 {
+    part1(input)
+  }
+
+  fn part2(input: &Vec<Command>) -> i64 {
+    part2(input)
+  }
+}
+
 /// Execute the initialization program. What is the sum of all values left in memory after it completes?
 ///
 /// # Example
@@ -8,62 +30,90 @@ use std::collections::HashMap;
 /// ```
 /// use advent_of_code_2020::day::day14::*;
 ///
-/// let input = "mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X
+/// let input = parse("mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X
 ///mem[8] = 11
 ///mem[7] = 101
-///mem[8] = 0".to_string();
+///mem[8] = 0").unwrap();
 /// let result = part1(&input);
 /// assert_eq!(result, 165);
 /// ```
-pub fn part1(i: &String) -> i64 {
-  let commands = parse(i);
-  let results = run_program_1(&commands);
+pub fn part1(commands: &[Command]) -> i64 {
+  let results = run_program_1(commands);
   results.values().sum()
 }
 
 #[derive(Clone, Debug, Default)]
-struct BitMask(Vec<Option<bool>>);
+pub struct BitMask(Vec<Option<bool>>);
 
 impl BitMask {
-  fn new(i: &String) -> Self {
-    Self(
-      i.chars()
-        .map(|c| match c {
-          '1' => Some(true),
-          '0' => Some(false),
-          _ => None,
-        })
-        .collect(),
-    )
+  fn new(input: &str, i: &str) -> Result<Self> {
+    if i.len() != 36 {
+      return Err(Error::at(input, i, "expected a 36 bit mask"));
+    }
+    i.char_indices()
+      .map(|(idx, c)| match c {
+        '1' => Ok(Some(true)),
+        '0' => Ok(Some(false)),
+        'X' => Ok(None),
+        _ => Err(Error::at(
+          input,
+          &i[idx..idx + c.len_utf8()],
+          "expected `0`, `1` or `X`",
+        )),
+      })
+      .collect::<Result<_>>()
+      .map(Self)
   }
 }
 
 #[derive(Clone, Debug)]
-enum Command {
+pub enum Command {
   SetBitMask(BitMask),
   SetMemory((i64, i64)),
 }
 
-fn parse(i: &String) -> Vec<Command> {
-  let mem_cmd_re = Regex::new(r"mem\[(\d+)\]").unwrap();
-  i.split("\n")
-    .filter_map(|line| {
-      let parts: Vec<String> = line.split(" = ").map(|s| s.to_string()).collect();
-      match (&parts.get(0), &parts.get(1)) {
-        (Some(cmd), Some(mask_str)) if *cmd == "mask" => {
-          Some(Command::SetBitMask(BitMask::new(mask_str)))
-        }
-        (Some(mem_cmd), Some(val_str)) if mem_cmd.starts_with("mem") => {
-          let addr: i64 = mem_cmd_re.captures(&mem_cmd).unwrap()[1].parse().unwrap();
-          Some(Command::SetMemory((addr, val_str.parse().unwrap())))
-        }
-        _ => None,
-      }
-    })
-    .collect()
+/// Values and addresses are 36 bit unsigned integers
+const WORD_LIMIT: i64 = 1 << 36;
+
+/// Parse one `mask = ...` or `mem[addr] = value` command per line. The first
+/// command must set the mask.
+pub fn parse(i: &str) -> Result<Vec<Command>> {
+  let mem_cmd_re = Regex::new(r"^mem\[(\d+)\]$").unwrap();
+  let mut commands = Vec::new();
+  for line in i.lines().filter(|line| !line.is_empty()) {
+    let (target, raw_value) = line
+      .split_once(" = ")
+      .ok_or_else(|| Error::at(i, line, "expected `<target> = <value>`"))?;
+    if target == "mask" {
+      commands.push(Command::SetBitMask(BitMask::new(i, raw_value)?));
+      continue;
+    }
+    let addr: i64 = mem_cmd_re
+      .captures(target)
+      .and_then(|groups| groups[1].parse().ok())
+      .ok_or_else(|| Error::at(i, target, "expected `mask` or `mem[<address>]`"))?;
+    if addr >= WORD_LIMIT {
+      return Err(Error::at(i, target, "addresses must fit in 36 bits"));
+    }
+    let value: i64 = raw_value
+      .parse()
+      .map_err(|_| Error::at(i, raw_value, "expected a number"))?;
+    if !(0..WORD_LIMIT).contains(&value) {
+      return Err(Error::at(i, raw_value, "values must fit in 36 bits"));
+    }
+    if commands.is_empty() {
+      return Err(Error::at(
+        i,
+        line,
+        "expected a mask before writing to memory",
+      ));
+    }
+    commands.push(Command::SetMemory((addr, value)));
+  }
+  Ok(commands)
 }
 
-fn run_program_1(commands: &Vec<Command>) -> HashMap<i64, i64> {
+fn run_program_1(commands: &[Command]) -> HashMap<i64, i64> {
   let mut bit_mask: BitMask = Default::default();
   let mut memory: HashMap<i64, i64> = HashMap::new();
   commands.iter().for_each(|cmd| match cmd {
@@ -91,20 +141,19 @@ fn run_program_1(commands: &Vec<Command>) -> HashMap<i64, i64> {
 /// ```
 /// use advent_of_code_2020::day::day14::*;
 ///
-/// let input = "mask = 000000000000000000000000000000X1001X
+/// let input = parse("mask = 000000000000000000000000000000X1001X
 ///mem[42] = 100
 ///mask = 00000000000000000000000000000000X0XX
-///mem[26] = 1".to_string();
+///mem[26] = 1").unwrap();
 /// let result = part2(&input);
 /// assert_eq!(result, 208);
 /// ```
-pub fn part2(i: &String) -> i64 {
-  let commands = parse(i);
-  let results = run_program_2(&commands);
+pub fn part2(commands: &[Command]) -> i64 {
+  let results = run_program_2(commands);
   results.values().sum()
 }
 
-fn run_program_2(commands: &Vec<Command>) -> HashMap<i64, i64> {
+fn run_program_2(commands: &[Command]) -> HashMap<i64, i64> {
   let mut bit_mask: BitMask = Default::default();
   let mut memory: HashMap<i64, i64> = HashMap::new();
   commands.iter().for_each(|cmd| match cmd {
@@ -119,13 +168,12 @@ fn run_program_2(commands: &Vec<Command>) -> HashMap<i64, i64> {
           None => 'X',
         })
         .collect();
-      let mut addrs: Vec<String> = Vec::new();
-      addrs.push(masked_addr);
+      let mut addrs: Vec<String> = vec![masked_addr];
       while addrs.iter().any(|s| s.contains('X')) {
         create_addresses_with_floats(&mut addrs);
       }
       addrs.iter().for_each(|addr| {
-        memory.insert(i64::from_str_radix(&addr, 2).unwrap(), *val);
+        memory.insert(i64::from_str_radix(addr, 2).unwrap(), *val);
       })
     }
   });
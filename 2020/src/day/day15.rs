@@ -1,5 +1,27 @@
 use std::collections::HashMap;
 
+use crate::{Error, Result, Solution};
+
+pub struct Day15;
+
+impl Solution for Day15 {
+  type Input = Vec<usize>;
+  type Part1 = usize;
+  type Part2 = usize;
+
+  fn parse(input: &str) -> Result<Vec<usize>> {
+    parse(input)
+  }
+
+  fn part1(input: &Vec<usize>) -> usize {
+    part1(input)
+  }
+
+  fn part2(input: &Vec<usize>) -> usize {
+    part2(input)
+  }
+}
+
 /// Memory Game.
 /// Provided a list of starting numbers, those are read in turn then play continues following these rules:
 /// - If that was the first time the number has been spoken, the current player says 0.
@@ -16,8 +38,20 @@ use std::collections::HashMap;
 /// let result = part1(&input);
 /// assert_eq!(result, 436);
 /// ```
-pub fn part1(starting_numbers: &Vec<usize>) -> usize {
-  find_nth(&starting_numbers, 2020)
+pub fn part1(starting_numbers: &[usize]) -> usize {
+  find_nth(starting_numbers, 2020)
+}
+
+/// Parse the comma separated starting numbers
+pub fn parse(i: &str) -> Result<Vec<usize>> {
+  i.trim()
+    .split(',')
+    .map(|s| {
+      s.trim()
+        .parse()
+        .map_err(|_| Error::at(i, s, "expected a number"))
+    })
+    .collect()
 }
 
 /// Find the 30000000th number spoken.
@@ -31,11 +65,11 @@ pub fn part1(starting_numbers: &Vec<usize>) -> usize {
 /// let result = part2(&input);
 /// assert_eq!(result, 175594);
 /// ```
-pub fn part2(starting_numbers: &Vec<usize>) -> usize {
-  find_nth(&starting_numbers, 30_000_000)
+pub fn part2(starting_numbers: &[usize]) -> usize {
+  find_nth(starting_numbers, 30_000_000)
 }
 
-fn find_nth(starting_numbers: &Vec<usize>, nth: usize) -> usize {
+fn find_nth(starting_numbers: &[usize], nth: usize) -> usize {
   let mut seen_numbers: HashMap<usize, usize> = HashMap::new();
 
   starting_numbers
@@ -49,14 +83,10 @@ fn find_nth(starting_numbers: &Vec<usize>, nth: usize) -> usize {
   let mut last_pushed = *starting_numbers.last().unwrap();
 
   for i in starting_numbers.len()..nth {
-    if !seen_numbers.contains_key(&last_pushed) {
-      seen_numbers.insert(last_pushed, i);
-      last_pushed = 0;
-    } else {
-      let when_seen = seen_numbers.get(&last_pushed).unwrap().clone();
-      seen_numbers.insert(last_pushed, i);
-      last_pushed = i - when_seen;
-    }
+    last_pushed = match seen_numbers.insert(last_pushed, i) {
+      Some(when_seen) => i - when_seen,
+      None => 0,
+    };
   }
   last_pushed
 }
@@ -1,5 +1,29 @@
-use std::collections::HashMap;
-use std::ops::Range;
+use itertools::Itertools;
+use std::iter;
+use std::ops::RangeInclusive;
+
+use crate::assignment::{self, AssignmentError};
+use crate::{Error, Result, Solution};
+
+pub struct Day16;
+
+impl Solution for Day16 {
+  type Input = Decoded;
+  type Part1 = i64;
+  type Part2 = i64;
+
+  fn parse(input: &str) -> Result<Decoded> {
+    decode(input)
+  }
+
+  fn part1(input: &Decoded) -> i64 {
+    part1(&input.notes)
+  }
+
+  fn part2(input: &Decoded) -> i64 {
+    part2(input)
+  }
+}
 
 /// Rules for ticket fields are inclusive ranges.
 /// The order of fields in your ticket and nearby tickets are the same,
@@ -12,7 +36,7 @@ use std::ops::Range;
 /// ```
 /// use advent_of_code_2020::day::day16::*;
 ///
-/// let input = "class: 1-3 or 5-7
+/// let input = parse("class: 1-3 or 5-7
 ///row: 6-11 or 33-44
 ///seat: 13-40 or 45-50
 ///
@@ -23,142 +47,375 @@ use std::ops::Range;
 ///7,3,47
 ///40,4,50
 ///55,2,20
-///38,6,12".to_string();
+///38,6,12").unwrap();
 /// let result = part1(&input);
 /// assert_eq!(result, 71);
 /// ```
-pub fn part1(i: &String) -> i64 {
-  let (rules, _, nearby_tickets) = parse(i);
-  let invalid_fields = find_invalid_fields(&rules, &nearby_tickets);
-  invalid_fields.iter().sum()
-}
-
-struct Rule {
-  ranges: Vec<Range<i64>>,
-}
-
-fn parse(i: &String) -> (Vec<Rule>, Vec<i64>, Vec<Vec<i64>>) {
-  let parts: Vec<&str> = i.split("\n\n").collect();
-  let rules = parts[0]
-    .split("\n")
-    .map(|line| {
-      let parts: Vec<&str> = line.split(":").collect();
-      let ranges = parts[1]
-        .split("or")
-        .map(|range_str| {
-          let start_end: Vec<i64> = range_str
-            .split("-")
-            .map(|s| s.trim().parse().unwrap())
-            .collect();
-          start_end[0]..(start_end[1] + 1)
-        })
-        .collect();
-      Rule { ranges }
-    })
-    .collect();
-  let my_ticket = parts[1]
-    .split("\n")
-    .last()
-    .unwrap()
-    .split(",")
-    .filter_map(|s| s.parse().ok())
-    .collect();
-  let tickets = parts[2]
-    .split("\n")
+pub fn part1(notes: &Notes) -> i64 {
+  notes
+    .nearby_tickets
+    .iter()
+    .flat_map(|ticket| invalid_values(&notes.rules, ticket))
+    .map(|invalid| invalid.value)
+    .sum()
+}
+
+/// A named field and the ranges its values must fall in
+#[derive(Clone, Debug)]
+pub struct Rule {
+  name: String,
+  ranges: Vec<RangeInclusive<i64>>,
+}
+
+impl Rule {
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  /// Whether `value` is in one of the rule's ranges
+  pub fn allows(&self, value: i64) -> bool {
+    self.ranges.iter().any(|range| range.contains(&value))
+  }
+}
+
+/// The field rules, your ticket, and the nearby tickets
+#[derive(Clone, Debug)]
+pub struct Notes {
+  rules: Vec<Rule>,
+  my_ticket: Vec<i64>,
+  nearby_tickets: Vec<Vec<i64>>,
+}
+
+impl Notes {
+  pub fn rules(&self) -> &[Rule] {
+    &self.rules
+  }
+
+  pub fn my_ticket(&self) -> &[i64] {
+    &self.my_ticket
+  }
+
+  pub fn nearby_tickets(&self) -> &[Vec<i64>] {
+    &self.nearby_tickets
+  }
+}
+
+/// Parse the rules, your ticket and nearby tickets sections
+pub fn parse(i: &str) -> Result<Notes> {
+  let mut sections = i.split("\n\n");
+  let mut next_section = |name: &str| {
+    sections
+      .next()
+      .ok_or_else(|| Error::at(i, &i[i.len()..], format!("expected {}", name)))
+  };
+  let rules: Vec<Rule> = next_section("the field rules")?
+    .lines()
+    .map(|line| parse_rule(i, line))
+    .collect::<Result<_>>()?;
+  let my_ticket_section = next_section("your ticket")?;
+  let my_ticket = my_ticket_section
+    .lines()
+    .nth(1)
+    .ok_or_else(|| Error::at(i, my_ticket_section, "expected your ticket"))
+    .and_then(|line| parse_ticket(i, line, &rules))?;
+  let nearby_tickets = next_section("nearby tickets")?
+    .lines()
     .skip(1)
-    .map(|line| line.split(",").filter_map(|s| s.parse().ok()).collect())
-    .collect();
-  (rules, my_ticket, tickets)
+    .filter(|line| !line.is_empty())
+    .map(|line| parse_ticket(i, line, &rules))
+    .collect::<Result<_>>()?;
+  Ok(Notes {
+    rules,
+    my_ticket,
+    nearby_tickets,
+  })
 }
 
-fn find_invalid_fields(rules: &Vec<Rule>, tickets: &Vec<Vec<i64>>) -> Vec<i64> {
-  tickets
-    .iter()
-    .flat_map(|ticket| {
-      ticket.iter().cloned().filter(|field| {
-        rules
-          .iter()
-          .all(|rule| rule.ranges.iter().all(|range| !range.contains(field)))
-      })
+/// Parse a `<name>: <start>-<end> or <start>-<end>` rule
+fn parse_rule(i: &str, line: &str) -> Result<Rule> {
+  let (name, raw_ranges) = line
+    .split_once(": ")
+    .ok_or_else(|| Error::at(i, line, "expected `<name>: <ranges>`"))?;
+  let ranges = raw_ranges
+    .split(" or ")
+    .map(|range_str| {
+      let (start, end) = range_str
+        .split_once('-')
+        .and_then(|(start, end)| Some((start.parse::<i64>().ok()?, end.parse::<i64>().ok()?)))
+        .ok_or_else(|| Error::at(i, range_str, "expected `<start>-<end>`"))?;
+      Ok(start..=end)
     })
+    .collect::<Result<_>>()?;
+  Ok(Rule {
+    name: name.into(),
+    ranges,
+  })
+}
+
+/// Parse a comma separated ticket with a value for each rule
+fn parse_ticket(i: &str, line: &str, rules: &[Rule]) -> Result<Vec<i64>> {
+  let ticket: Vec<i64> = line
+    .split(',')
+    .map(|s| s.parse().map_err(|_| Error::at(i, s, "expected a number")))
+    .collect::<Result<_>>()?;
+  if ticket.len() != rules.len() {
+    return Err(Error::at(
+      i,
+      line,
+      format!("expected {} values", rules.len()),
+    ));
+  }
+  Ok(ticket)
+}
+
+/// A value on a ticket that no rule allows
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvalidValue {
+  pub column: usize,
+  pub value: i64,
+}
+
+/// The values on `ticket` that don't fit any of the rules
+pub fn invalid_values(rules: &[Rule], ticket: &[i64]) -> Vec<InvalidValue> {
+  ticket
+    .iter()
+    .enumerate()
+    .filter(|(_, &value)| !rules.iter().any(|rule| rule.allows(value)))
+    .map(|(column, &value)| InvalidValue { column, value })
     .collect()
 }
 
 /// Discard the tickets with invalid fields.
 /// Use the remaining tickets to determine the field order.
 ///
-/// Find the product of the fields beginning with `departure` (the first 5 rules)
-pub fn part2(i: &String) -> i64 {
-  let (rules, my_ticket, nearby_tickets) = parse(i);
-  let valid_tickets = filter_invalid_tickets(&rules, &nearby_tickets);
-  let field_order = find_field_order(&rules, &valid_tickets);
-  println!("{:?}", field_order);
-  (0..6)
-    .map(|i| {
-      my_ticket[*field_order
-        .iter()
-        .find(|(_, &rule_idx)| rule_idx == i)
-        .unwrap()
-        .0]
-    })
+/// Find the product of the fields on your ticket beginning with `departure`
+pub fn part2(decoded: &Decoded) -> i64 {
+  decoded
+    .notes
+    .rules
+    .iter()
+    .zip(&decoded.order)
+    .filter(|(rule, _)| rule.name.starts_with("departure"))
+    .map(|(_, &column)| decoded.notes.my_ticket[column])
     .product()
 }
 
-fn filter_invalid_tickets(rules: &Vec<Rule>, tickets: &Vec<Vec<i64>>) -> Vec<Vec<i64>> {
-  tickets
-    .iter()
-    .cloned()
-    .filter(|ticket| {
-      !ticket.iter().any(|field| {
-        rules
-          .iter()
-          .all(|rule| rule.ranges.iter().all(|range| !range.contains(field)))
-      })
-    })
-    .collect()
+/// The notes with the ticket column each rule applies to
+#[derive(Clone, Debug)]
+pub struct Decoded {
+  notes: Notes,
+  order: Vec<usize>,
 }
 
-fn find_field_order(rules: &Vec<Rule>, tickets: &Vec<Vec<i64>>) -> HashMap<usize, usize> {
-  let potentials = rules
-    .iter()
-    .map(|rule| {
-      (0..rules.len())
-        .filter(|idx| {
-          tickets
-            .iter()
-            .map(|t| t[*idx])
-            .all(|field| rule.ranges.iter().any(|r| r.contains(&field)))
-        })
-        .collect()
-    })
-    .collect();
-  solve(&potentials)
+impl Decoded {
+  pub fn notes(&self) -> &Notes {
+    &self.notes
+  }
+
+  /// The ticket column for each rule, in rule order
+  pub fn order(&self) -> &[usize] {
+    &self.order
+  }
 }
 
-fn solve(potentials: &Vec<Vec<usize>>) -> HashMap<usize, usize> {
-  let mut solved_fields = HashMap::new();
-  loop {
-    if solved_fields.len() == potentials.len() {
-      break;
-    }
-    let remaining_potentials = potentials
+/// Parse the notes and work out the field order, pointing at the rule that
+/// no column is left for or that more than one column fits.
+///
+/// # Example
+///
+/// ```
+/// use advent_of_code_2020::day::day16::*;
+///
+/// let input = "class: 0-1 or 4-19
+///row: 0-5 or 8-19
+///seat: 0-13 or 16-19
+///
+///your ticket:
+///11,12,13
+///
+///nearby tickets:
+///3,9,18
+///15,1,5
+///5,14,9";
+/// assert_eq!(decode(input).unwrap().order(), &[1, 0, 2]);
+///
+/// let err = decode(&input.replace("seat: 0-13", "seat: 0-19")).unwrap_err();
+/// assert_eq!(
+///   err.to_string(),
+///   "field could be ticket column 2 or 3 at line 1, column 1: \"class: 0-1 or 4-19\""
+/// );
+/// ```
+pub fn decode(i: &str) -> Result<Decoded> {
+  let notes = parse(i)?;
+  let order = field_order(&notes).map_err(|err| {
+    let (rule, message) = match err {
+      AssignmentError::NoAssignment(unmatched) => {
+        (unmatched[0], "no column is left for this field".to_string())
+      }
+      AssignmentError::Ambiguous(first, second) => {
+        let rule = (0..first.len())
+          .find(|&rule| first[rule] != second[rule])
+          .expect("two different orders differ somewhere");
+        let message = format!(
+          "field could be ticket column {} or {}",
+          first[rule].min(second[rule]) + 1,
+          first[rule].max(second[rule]) + 1
+        );
+        (rule, message)
+      }
+    };
+    // The rules are the first lines of the notes
+    let line = i.lines().nth(rule).unwrap_or(i);
+    Error::at(i, line, message)
+  })?;
+  Ok(Decoded { notes, order })
+}
+
+/// A ticket with its values under their field names, in rule order
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ticket<'a> {
+  pub fields: Vec<(&'a str, i64)>,
+}
+
+impl Ticket<'_> {
+  /// The value of the field called `name`
+  pub fn get(&self, name: &str) -> Option<i64> {
+    self
+      .fields
       .iter()
-      .map(|p| {
-        p.iter()
-          .cloned()
-          .filter(|i| !solved_fields.contains_key(i))
-          .collect()
-      })
-      .collect();
-    reduce_potentials(&mut solved_fields, &remaining_potentials);
-  }
-  solved_fields
-}
-
-fn reduce_potentials(solved_fields: &mut HashMap<usize, usize>, potentials: &Vec<Vec<usize>>) {
-  potentials.iter().enumerate().for_each(|(idx, p)| {
-    if p.len() == 1 {
-      solved_fields.insert(p[0], idx);
+      .find(|(field, _)| *field == name)
+      .map(|&(_, value)| value)
+  }
+}
+
+/// A nearby ticket that was thrown out, and the values that gave it away
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidTicket {
+  /// Where the ticket is in the nearby tickets
+  pub index: usize,
+  pub values: Vec<InvalidValue>,
+}
+
+/// The notes translated using the field order the valid tickets agree on
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Report<'a> {
+  /// The rule for each column of a ticket, by name
+  pub columns: Vec<&'a str>,
+  pub mine: Ticket<'a>,
+  /// Each valid nearby ticket with where it is in the nearby tickets
+  pub valid: Vec<(usize, Ticket<'a>)>,
+  pub invalid: Vec<InvalidTicket>,
+}
+
+impl Report<'_> {
+  /// The valid nearby tickets as CSV, with a header of the field names in
+  /// rule order and each ticket's index in the nearby tickets first
+  pub fn to_csv(&self) -> String {
+    let names = self.mine.fields.iter().map(|(name, _)| csv_field(name));
+    let mut out = iter::once("ticket".to_string()).chain(names).join(",");
+    out.push('\n');
+    for (index, ticket) in &self.valid {
+      let values = ticket.fields.iter().map(|(_, value)| value.to_string());
+      out.push_str(&iter::once(index.to_string()).chain(values).join(","));
+      out.push('\n');
+    }
+    out
+  }
+}
+
+/// Quote a CSV field if it has a comma, quote or line break in it
+fn csv_field(field: &str) -> String {
+  if field.contains(&[',', '"', '\n', '\r'][..]) {
+    format!("\"{}\"", field.replace('"', "\"\""))
+  } else {
+    field.into()
+  }
+}
+
+/// Set aside the invalid nearby tickets, work out which column is which
+/// field from the rest, and decode every ticket with it.
+///
+/// # Example
+///
+/// ```
+/// use advent_of_code_2020::day::day16::*;
+///
+/// let notes = parse("class: 0-1 or 4-19
+///row: 0-5 or 8-19
+///seat: 0-13 or 16-19
+///
+///your ticket:
+///11,12,13
+///
+///nearby tickets:
+///3,9,18
+///15,1,5
+///20,1,5
+///5,14,9").unwrap();
+/// let report = report(&notes).unwrap();
+/// assert_eq!(report.columns, vec!["row", "class", "seat"]);
+/// assert_eq!(report.mine.get("class"), Some(12));
+/// assert_eq!(report.invalid.len(), 1);
+/// assert_eq!(report.invalid[0].index, 2);
+/// assert_eq!(report.invalid[0].values, vec![InvalidValue { column: 0, value: 20 }]);
+/// assert_eq!(
+///   report.to_csv(),
+///   "ticket,class,row,seat\n0,9,3,18\n1,1,15,5\n3,14,5,9\n"
+/// );
+/// ```
+pub fn report(notes: &Notes) -> std::result::Result<Report<'_>, AssignmentError> {
+  let mut valid = Vec::new();
+  let mut invalid = Vec::new();
+  for (index, ticket) in notes.nearby_tickets.iter().enumerate() {
+    let values = invalid_values(&notes.rules, ticket);
+    if values.is_empty() {
+      valid.push((index, ticket));
+    } else {
+      invalid.push(InvalidTicket { index, values });
     }
+  }
+
+  let order = field_order(notes)?;
+  let translate = |ticket: &[i64]| Ticket {
+    fields: notes
+      .rules
+      .iter()
+      .zip(&order)
+      .map(|(rule, &column)| (rule.name(), ticket[column]))
+      .collect(),
+  };
+
+  let mut columns = vec![""; order.len()];
+  for (rule, &column) in notes.rules.iter().zip(&order) {
+    columns[column] = rule.name();
+  }
+  Ok(Report {
+    columns,
+    mine: translate(&notes.my_ticket),
+    valid: valid
+      .into_iter()
+      .map(|(index, ticket)| (index, translate(ticket)))
+      .collect(),
+    invalid,
   })
 }
+
+/// The ticket column each rule applies to, going by the nearby tickets
+/// with no invalid values
+pub fn field_order(notes: &Notes) -> std::result::Result<Vec<usize>, AssignmentError> {
+  let tickets: Vec<&Vec<i64>> = notes
+    .nearby_tickets
+    .iter()
+    .filter(|ticket| invalid_values(&notes.rules, ticket).is_empty())
+    .collect();
+  let potentials: Vec<Vec<usize>> = notes
+    .rules
+    .iter()
+    .map(|rule| {
+      (0..notes.rules.len())
+        .filter(|&idx| tickets.iter().all(|ticket| rule.allows(ticket[idx])))
+        .collect()
+    })
+    .collect();
+  assignment::assign(&potentials)
+}
@@ -1,3 +1,26 @@
+use crate::expression::{self, Assoc, Expr, Op, Precedence};
+use crate::{Error, Result, Solution};
+
+pub struct Day18;
+
+impl Solution for Day18 {
+  type Input = Homework;
+  type Part1 = i64;
+  type Part2 = i64;
+
+  fn parse(input: &str) -> Result<Homework> {
+    parse(input)
+  }
+
+  fn part1(input: &Homework) -> i64 {
+    part1(input)
+  }
+
+  fn part2(input: &Homework) -> i64 {
+    part2(input)
+  }
+}
+
 /// Maths but operator precedence is just left to right
 /// parenthesis still override order.
 ///
@@ -6,18 +29,46 @@
 /// ```
 /// use advent_of_code_2020::day::day18::*;
 ///
-/// let input = "((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2".to_string();
+/// let input = parse("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2").unwrap();
 /// let result = part1(&input);
 /// assert_eq!(result, 13632);
 /// ```
-pub fn part1(i: &String) -> i64 {
-  i.split("\n")
-    .filter_map(|line| match line.trim().len() {
-      0 => None,
-      _ => Some(arithmetic::parens_priority(line)),
-    })
-    .sum::<Result<i64, _>>()
-    .unwrap()
+pub fn part1(homework: &Homework) -> i64 {
+  sum(&homework.left_to_right)
+}
+
+/// Each line of homework parsed with each part's precedence
+#[derive(Clone, Debug, PartialEq)]
+pub struct Homework {
+  left_to_right: Vec<Expr>,
+  addition_first: Vec<Expr>,
+}
+
+/// Parse one expression per line, skipping blank lines. Every expression
+/// and the sum of them must evaluate under both parts' precedence without
+/// overflowing or dividing by zero.
+pub fn parse(i: &str) -> Result<Homework> {
+  let mut homework = Homework {
+    left_to_right: Vec::new(),
+    addition_first: Vec::new(),
+  };
+  let tables = [
+    (Precedence::left_to_right(), &mut homework.left_to_right),
+    (addition_first(), &mut homework.addition_first),
+  ];
+  for (table, expressions) in tables {
+    let mut total: i64 = 0;
+    for line in i.lines().filter(|line| !line.trim().is_empty()) {
+      let expr = expression::parse_within(i, line, &table)?;
+      total = expr
+        .eval()
+        .ok_or_else(|| Error::at(i, line, "expression overflows or divides by zero"))?
+        .checked_add(total)
+        .ok_or_else(|| Error::at(i, line, "sum of the expressions overflows"))?;
+      expressions.push(expr);
+    }
+  }
+  Ok(homework)
 }
 
 /// Now addition has precedence over multiplication
@@ -27,41 +78,30 @@ pub fn part1(i: &String) -> i64 {
 /// ```
 /// use advent_of_code_2020::day::day18::*;
 ///
-/// let input = "((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2".to_string();
-/// let result = part1(&input);
-/// assert_eq!(result, 13632);
+/// let input = parse("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2").unwrap();
+/// let result = part2(&input);
+/// assert_eq!(result, 23340);
 /// ```
-pub fn part2(i: &String) -> i64 {
-  i.split("\n")
-    .filter_map(|line| match line.trim().len() {
-      0 => None,
-      _ => Some(arithmetic::parens_addition_priority(line)),
-    })
-    .sum::<Result<i64, _>>()
-    .unwrap()
+pub fn part2(homework: &Homework) -> i64 {
+  sum(&homework.addition_first)
 }
 
-peg::parser! {
-    grammar arithmetic() for str {
-        rule num() -> i64 = _ n:$(['0'..='9']) _ { n.parse().unwrap() }
-
-        pub rule parens_priority() -> i64 = precedence!{
-            x:(@) "+" y:@ { x + y }
-            x:(@) "*" y:@ { x * y }
-            --
-            n:num() { n }
-            _ "(" e:parens_priority() ")" _ { e }
-        }
-
-        pub rule parens_addition_priority() -> i64 = precedence!{
-            x:(@) "*" y:@ { x * y }
-            --
-            x:(@) "+" y:@ { x + y }
-            --
-            n:num() { n }
-            _ "(" e:parens_addition_priority() ")" _ { e }
-        }
+/// `+` and `-` before `*` and `/`
+pub fn addition_first() -> Precedence {
+  Precedence::new()
+    .with(Op::Add, 2, Assoc::Left)
+    .with(Op::Sub, 2, Assoc::Left)
+    .with(Op::Mul, 1, Assoc::Left)
+    .with(Op::Div, 1, Assoc::Left)
+}
 
-        rule _() = quiet!{[c if c.is_whitespace()]*}
-    }
+fn sum(expressions: &[Expr]) -> i64 {
+  expressions
+    .iter()
+    .map(|expr| {
+      expr
+        .eval()
+        .expect("parse checks every expression evaluates")
+    })
+    .sum()
 }
@@ -1,4 +1,25 @@
-use std::collections::HashMap;
+use crate::grammar::{parse_rule, Grammar};
+use crate::{Error, Result, Solution};
+
+pub struct Day19;
+
+impl Solution for Day19 {
+  type Input = Rules;
+  type Part1 = usize;
+  type Part2 = usize;
+
+  fn parse(input: &str) -> Result<Rules> {
+    parse(input)
+  }
+
+  fn part1(input: &Rules) -> usize {
+    part1(input)
+  }
+
+  fn part2(input: &Rules) -> usize {
+    part2(input)
+  }
+}
 
 /// Initial section of input are numbered rules.
 /// Rules can match other rules or be specific strings.
@@ -10,7 +31,7 @@ use std::collections::HashMap;
 /// ```
 /// use advent_of_code_2020::day::day19::*;
 ///
-/// let input = r#"0: 4 1 5
+/// let input = parse(r#"0: 4 1 5
 ///1: 2 3 | 3 2
 ///2: 4 4 | 5 5
 ///3: 4 5 | 5 4
@@ -21,122 +42,113 @@ use std::collections::HashMap;
 ///bababa
 ///abbbab
 ///aaabbb
-///aaaabbb"#.to_string();
+///aaaabbb"#).unwrap();
 /// let result = part1(&input);
 /// assert_eq!(result, 2);
 /// ```
-pub fn part1(i: &String) -> i64 {
-  solve(i)
-}
-
-enum Rule {
-  Ref(u64),
-  Or(Box<Rule>, Box<Rule>),
-  Ch(char),
-  And3(Box<Rule>, Box<Rule>, Box<Rule>),
-  And(Box<Rule>, Box<Rule>),
+pub fn part1(i: &Rules) -> usize {
+  count_matches(&i.grammar, &i.messages)
 }
 
-impl Rule {
-  fn matches<'a>(&self, rules: &'a HashMap<u64, Rule>, unparsed: &'a [char]) -> Vec<&'a [char]> {
-    if unparsed.is_empty() {
-      return vec![];
-    }
-    match self {
-      Rule::Ref(i) => rules.get(i).unwrap().matches(rules, unparsed),
-      Rule::Or(a, b) => {
-        let mut r = Vec::new();
-        for a in a.matches(rules, unparsed).into_iter() {
-          r.push(a);
-        }
-        for b in b.matches(rules, unparsed).into_iter() {
-          r.push(b);
-        }
-
-        r
-      }
-      Rule::Ch(c) => {
-        if unparsed[0] == *c {
-          vec![&unparsed[1..]]
-        } else {
-          vec![]
-        }
-      }
-      Rule::And3(a, b, c) => {
-        let mut r = Vec::new();
-        for m in a.matches(rules, unparsed).into_iter() {
-          for n in b.matches(rules, m) {
-            for o in c.matches(rules, n) {
-              r.push(o);
-            }
-          }
-        }
-        r
-      }
-      Rule::And(a, b) => {
-        let mut r = Vec::new();
-        for m in a.matches(rules, unparsed).into_iter() {
-          for n in b.matches(rules, m) {
-            r.push(n);
-          }
-        }
-        r
-      }
-    }
-  }
+/// Replace rules 8 and 11 with `8: 42 | 42 8` and `11: 42 31 | 42 11 31`,
+/// which loop back on themselves.
+///
+/// Find the number of messages that completely match rule 0.
+///
+/// # Example
+///
+/// ```
+/// use advent_of_code_2020::day::day19::*;
+///
+/// let input = parse(r#"42: 9 14 | 10 1
+///9: 14 27 | 1 26
+///10: 23 14 | 28 1
+///1: "a"
+///11: 42 31
+///5: 1 14 | 15 1
+///19: 14 1 | 14 14
+///12: 24 14 | 19 1
+///16: 15 1 | 14 14
+///31: 14 17 | 1 13
+///6: 14 14 | 1 14
+///2: 1 24 | 14 4
+///0: 8 11
+///13: 14 3 | 1 12
+///15: 1 | 14
+///17: 14 2 | 1 7
+///23: 25 1 | 22 14
+///28: 16 1
+///4: 1 1
+///20: 14 14 | 1 15
+///3: 5 14 | 16 1
+///27: 1 6 | 14 18
+///14: "b"
+///21: 14 1 | 1 14
+///25: 1 1 | 1 14
+///22: 14 14
+///8: 42
+///26: 14 22 | 1 20
+///18: 15 15
+///7: 14 5 | 1 21
+///24: 14 1
+///
+///abbbbbabbbaaaababbaabbbbabababbbabbbbbbabaaaa
+///bbabbbbaabaabba
+///babbbbaabbbbbabbbbbbaabaaabaaa
+///aaabbbbbbaaaabaababaabababbabaaabbababababaaa
+///bbbbbbbaaaabbbbaaabbabaaa
+///bbbababbbbaaaaaaaabbababaaababaabab
+///ababaaaaaabaaab
+///ababaaaaabbbaba
+///baabbaaaabbaaaababbaababb
+///abbbbabbbbaaaababbbbbbaaaababb
+///aaaaabbaabaaaaababaa
+///aaaabbaaaabbaaa
+///aaaabbaabbaaaaaaabbbabbbaaabbaabaaa
+///babaaabbbaaabaababbaabababaaab
+///aabbbbbaabbbaaaaaabbbbbababaaaaabbaaabba"#).unwrap();
+/// assert_eq!(part1(&input), 3);
+/// assert_eq!(part2(&input), 12);
+/// ```
+pub fn part2(i: &Rules) -> usize {
+  let mut grammar = i.grammar.clone();
+  grammar.insert(8, parse_rule("42 | 42 8").expect("rule 8 is valid"));
+  grammar.insert(
+    11,
+    parse_rule("42 31 | 42 11 31").expect("rule 11 is valid"),
+  );
+  count_matches(&grammar, &i.messages)
 }
 
-fn parse(s: &str) -> Rule {
-  if s.contains(" | ") {
-    let parts: Vec<_> = s.split(" | ").collect();
-
-    Rule::Or(Box::new(parse(parts[0])), Box::new(parse(parts[1])))
-  } else if s.starts_with('"') {
-    Rule::Ch(s.chars().nth(1).unwrap())
-  } else if s.contains(' ') {
-    let parts: Vec<_> = s.split(' ').collect();
-    if parts.len() == 3 {
-      Rule::And3(
-        Box::new(parse(parts[0])),
-        Box::new(parse(parts[1])),
-        Box::new(parse(parts[2])),
-      )
-    } else if parts.len() == 2 {
-      Rule::And(Box::new(parse(parts[0])), Box::new(parse(parts[1])))
-    } else {
-      panic!();
-    }
-  } else if let Ok(i) = s.parse() {
-    Rule::Ref(i)
-  } else {
-    eprintln!("{}", s);
-    panic!();
-  }
+/// Rules keyed by their number, and the messages to check against rule 0
+pub struct Rules {
+  grammar: Grammar,
+  messages: Vec<String>,
 }
 
-pub fn solve(input: &str) -> i64 {
+/// Parse the numbered rules followed by a blank line and the messages
+pub fn parse(input: &str) -> Result<Rules> {
   let mut parts = input.trim().split("\n\n");
-  let rules = parts.next().unwrap();
-
-  let mut r = HashMap::new();
-
-  for rule in rules.trim().split('\n') {
-    let mut parts = rule.split(": ");
-    let id = parts.next().unwrap().parse::<u64>().unwrap();
-    r.insert(id, parse(&parts.next().unwrap()));
+  let rules = parts.next().unwrap_or_default();
+  let grammar = Grammar::parse_within(input, rules)?;
+  if grammar.get(0).is_none() {
+    return Err(Error::at(input, rules, "expected a rule 0"));
   }
 
-  let msgs = parts.next().unwrap();
-  let mut c = 0;
-  for msg in msgs.split('\n') {
-    let msg: Vec<_> = msg.chars().collect();
-    for m in r.get(&0).unwrap().matches(&r, &msg).into_iter() {
-      if m.is_empty() {
-        c += 1;
-        break;
-      }
-    }
-  }
+  let messages = parts
+    .next()
+    .ok_or_else(|| Error::at(input, &input[input.len()..], "expected messages"))?
+    .lines()
+    .map(|msg| msg.into())
+    .collect();
+  Ok(Rules { grammar, messages })
+}
 
-  c
+/// How many messages match rule 0, through a regex unless rule 0 is recursive
+pub fn count_matches(grammar: &Grammar, messages: &[String]) -> usize {
+  let matcher = grammar.matcher(0);
+  messages
+    .iter()
+    .filter(|message| matcher.matches(message))
+    .count()
 }
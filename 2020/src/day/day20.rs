@@ -1,6 +1,33 @@
-use itertools::{izip, Itertools};
-use std::collections::HashMap;
-use std::convert::{TryFrom, TryInto};
+use itertools::Itertools;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::grid::{Grid, Position};
+use crate::{Error, Result, Solution};
+
+pub struct Day20;
+
+impl Solution for Day20 {
+  type Input = Assembly;
+  type Part1 = i64;
+  type Part2 = usize;
+
+  fn parse(input: &str) -> Result<Assembly> {
+    let tiles = parse(input)?;
+    assemble(&tiles).map_err(|err| {
+      let first = input.trim_start().lines().next().unwrap_or("");
+      Error::at(input, first, err.to_string())
+    })
+  }
+
+  fn part1(input: &Assembly) -> i64 {
+    part1(input)
+  }
+
+  fn part2(input: &Assembly) -> usize {
+    part2(input)
+  }
+}
 
 /// Provided an array of images. Flip, rotate and arrange the images
 /// so that all adjacent border line up (they should match exactly).
@@ -13,7 +40,8 @@ use std::convert::{TryFrom, TryInto};
 /// ```
 /// use advent_of_code_2020::day::day20::*;
 ///
-/// let input = r"Tile 2311:
+/// // `*` stands in for `#` so rustdoc keeps the rows that start with one
+/// let input = parse(&r"Tile 2311:
 ///..**.*..*.
 ///**..*.....
 ///*...**..*.
@@ -119,164 +147,101 @@ use std::convert::{TryFrom, TryInto};
 ///*.*****.**
 ///..*.***...
 ///..*.......
-///..*.***...".to_string();
-/// let result = part1(&input);
+///..*.***...".replace('*', "#")).unwrap();
+/// let result = part1(&assemble(&input).unwrap());
 /// assert_eq!(result, 20899048083289);
 /// ```
-pub fn part1(i: &String) -> i64 {
-  let images: HashMap<i64, Image<10>> = parse(i);
-  let edges = find_corners(&images);
-  edges.keys().product()
+pub fn part1(assembly: &Assembly) -> i64 {
+  assembly.corners().iter().product()
 }
 
-type Pixels<const N: usize> = [[bool; N]; N];
+/// Puzzle tiles keyed by their ID
+pub type Tiles = HashMap<i64, Image>;
 
-#[derive(Clone, Copy, Debug)]
-struct Image<const N: usize> {
-  pixels: Pixels<N>,
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+  pixels: Grid<bool>,
 }
 
-impl<const N: usize> TryFrom<Vec<Vec<bool>>> for Image<N> {
-  type Error = &'static str; // whatever
-
-  fn try_from(value: Vec<Vec<bool>>) -> Result<Self, Self::Error> {
-    let pixels = value
-      .into_iter()
-      .map(|row| row.try_into().map_err(|_| "bad width"))
-      .collect::<Result<Vec<[bool; N]>, &str>>()?
-      .try_into()
-      .map_err(|_| "bad height")?;
-
-    Ok(Self { pixels })
-  }
-}
-
-impl<const N: usize> Image<N> {
-  fn flip_vertical(&self) -> Self {
-    let mut pixels = [[false; N]; N];
-    for y in 0..N {
-      pixels[y] = self.pixels[(N - 1) - y];
-    }
-    Self { pixels }
-  }
-
-  fn rotate(&self) -> Self {
-    let mut pixels = [[false; N]; N];
-    for y in 0..N {
-      for x in 0..N {
-        pixels[y][x] = self.pixels[(N - 1) - x][y];
-      }
-    }
-    Self { pixels }
-  }
-
+impl Image {
   fn orientations(&self) -> Vec<Self> {
-    let mut current = self.clone();
-    let mut all = Vec::new();
-    for _ in 0..4 {
-      all.push(current);
-      current = Self {
-        ..current.clone().rotate()
-      };
-    }
-    current = current.clone().flip_vertical();
-    for _ in 0..4 {
-      all.push(current);
-      current = current.clone().rotate();
-    }
-    all.iter().cloned().map(|i| i.clone()).collect()
-  }
-
-  fn row(&self, row: usize) -> [bool; N] {
-    self.pixels[row]
-  }
-
-  fn column(&self, column: usize) -> [bool; N] {
-    let mut pixels = [false; N];
-    for y in 0..N {
-      pixels[y] = self.pixels[y][column]
-    }
-    pixels
+    self
+      .pixels
+      .orientations()
+      .into_iter()
+      .map(|pixels| Self { pixels })
+      .collect()
   }
 
-  fn edges(&self) -> [[bool; N]; 4] {
+  /// The top, bottom, left and right borders as bitmasks, read left to
+  /// right and top to bottom with the first pixel in the highest bit
+  fn borders(&self) -> [u64; 4] {
+    let last = self.pixels.width() - 1;
     [
-      self.row(0),
-      self.row(N - 1),
-      self.column(0),
-      self.column(N - 1),
+      bitmask(self.pixels.row(0)),
+      bitmask(self.pixels.row(last)),
+      bitmask(self.pixels.column(0)),
+      bitmask(self.pixels.column(last)),
     ]
   }
+}
 
-  fn fits_above(&self, other: &Self) -> bool {
-    self.pixels[N - 1] == other.pixels[0]
-  }
-
-  fn fits_below(&self, other: &Self) -> bool {
-    other.fits_above(self)
-  }
-
-  fn fits_left(&self, other: &Self) -> bool {
-    (0..N).all(|y| self.pixels[y][N - 1] == other.pixels[y][0])
-  }
-
-  fn fits_right(&self, other: &Self) -> bool {
-    other.fits_left(self)
-  }
+fn bitmask<'a>(pixels: impl IntoIterator<Item = &'a bool>) -> u64 {
+  pixels
+    .into_iter()
+    .fold(0, |mask, &set| mask << 1 | set as u64)
 }
 
-fn parse<const N: usize>(i: &String) -> HashMap<i64, Image<N>> {
-  i.trim()
-    .split("\n\n")
-    .map(|tile| {
-      let parts: Vec<&str> = tile.split("\n").collect();
-      let id: i64 = parts[0]
-        .split(" ")
-        .last()
-        .unwrap()
-        .replace(":", "")
-        .parse()
-        .unwrap();
-      let mut pixels = [[false; N]; N];
-      parts.iter().skip(1).enumerate().for_each(|(y, line)| {
-        line.chars().enumerate().for_each(|(x, c)| match c {
-          '.' => pixels[y][x] = false,
-          _ => pixels[y][x] = true,
-        });
-      });
-      (id, Image { pixels })
-    })
-    .collect()
+/// The same key for a border whichever way round it is read
+fn canonical(border: u64, size: usize) -> u64 {
+  let reversed = border.reverse_bits() >> (64 - size);
+  border.min(reversed)
 }
 
-fn find_corners<const N: usize>(images: &HashMap<i64, Image<N>>) -> HashMap<i64, Image<N>> {
-  images
-    .iter()
-    .filter(|(id, image)| {
-      image
-        .edges()
-        .iter()
-        .filter(|edge| {
-          let reverse: [bool; N] = edge
-            .iter()
-            .cloned()
-            .rev()
-            .collect::<Vec<bool>>()
-            .try_into()
-            .unwrap();
-          images
-            .iter()
-            .filter(|(other_id, _)| other_id != id)
-            .all(|(_, other_image)| {
-              let other_edges = other_image.edges();
-              !(other_edges.contains(edge) || other_edges.contains(&reverse))
-            })
-        })
-        .count()
-        == 2
-    })
-    .map(|(id, image)| (*id, *image))
-    .collect()
+/// Borders are matched as bitmasks, so tiles can be at most this wide
+pub const MAX_TILE_SIZE: usize = 64;
+
+/// Parse the blank line separated `Tile <id>:` blocks of pixels. Tiles are
+/// square, all the same size as the first, and each has its own id.
+pub fn parse(i: &str) -> Result<Tiles> {
+  let mut size = None;
+  let mut tiles = Tiles::new();
+  for tile in i.trim().split("\n\n") {
+    let (header, body) = tile.split_once('\n').unwrap_or((tile, ""));
+    let id: i64 = header
+      .strip_prefix("Tile ")
+      .and_then(|id| id.strip_suffix(':'))
+      .and_then(|id| id.parse().ok())
+      .ok_or_else(|| Error::at(i, header, "expected `Tile <id>:`"))?;
+    if tiles.contains_key(&id) {
+      return Err(Error::at(i, header, "tile id is already used"));
+    }
+    let pixels = Grid::parse_within(i, body, |c| match c {
+      '.' => Some(false),
+      '#' => Some(true),
+      _ => None,
+    })?;
+    let size = *size.get_or_insert(pixels.width());
+    if size < 3 {
+      return Err(Error::at(i, header, "expected at least 3x3 pixels"));
+    }
+    if size > MAX_TILE_SIZE {
+      return Err(Error::at(
+        i,
+        header,
+        format!("expected at most {0}x{0} pixels", MAX_TILE_SIZE),
+      ));
+    }
+    if pixels.width() != size || pixels.height() != size {
+      return Err(Error::at(
+        i,
+        header,
+        format!("expected {0}x{0} pixels", size),
+      ));
+    }
+    tiles.insert(id, Image { pixels });
+  }
+  Ok(tiles)
 }
 
 /// Now that the images are assembled you can find sea monsters.
@@ -295,7 +260,8 @@ fn find_corners<const N: usize>(images: &HashMap<i64, Image<N>>) -> HashMap<i64,
 /// ```
 /// use advent_of_code_2020::day::day20::*;
 ///
-/// let input = r"Tile 2311:
+/// // `*` stands in for `#` so rustdoc keeps the rows that start with one
+/// let input = parse(&r"Tile 2311:
 ///..**.*..*.
 ///**..*.....
 ///*...**..*.
@@ -401,135 +367,604 @@ fn find_corners<const N: usize>(images: &HashMap<i64, Image<N>>) -> HashMap<i64,
 ///*.*****.**
 ///..*.***...
 ///..*.......
-///..*.***...".to_string();
-/// let result = part2(&input);
+///..*.***...".replace('*', "#")).unwrap();
+/// let result = part2(&assemble(&input).unwrap());
 /// assert_eq!(result, 273);
 /// ```
-pub fn part2(i: &String) -> usize {
-  let images: HashMap<i64, Image<10>> = parse(i);
-  let (assembled, _) = assemble(&images);
-  let (min_x, max_x) = assembled
-    .keys()
-    .map(|(x, _)| *x)
-    .minmax()
-    .into_option()
-    .unwrap();
-  let (min_y, max_y) = assembled
-    .keys()
-    .map(|(_, y)| *y)
-    .minmax()
-    .into_option()
-    .unwrap();
-
-  let mut stitched = Vec::new();
-
-  for tile_y in min_y..=max_y {
-    let mut rows = vec![Vec::new(); 8];
-    for tile_x in min_x..=max_x {
-      let tile = &assembled[&(tile_x, tile_y)];
-      for y in 1..9 {
-        rows[y - 1].extend(tile.pixels[y][1..9].iter().copied());
+pub fn part2(assembly: &Assembly) -> usize {
+  let image = assembly.image();
+  let monster = Pattern::parse(SEA_MONSTER).expect("the sea monster is a valid pattern");
+  roughness(&image, &find_oriented(&image, &monster))
+}
+
+/// Names for the orientations of `Grid::orientations`: `r` and the
+/// clockwise rotation, or `f` when the tile was flipped top to bottom first
+pub const ORIENTATION_NAMES: [&str; 8] = ["r0", "r90", "r180", "r270", "f0", "f90", "f180", "f270"];
+
+/// A tile in its place in the assembled picture
+#[derive(Clone, Debug, PartialEq)]
+pub struct Placement {
+  pub id: i64,
+  /// Index of the tile's orientation, as in `Grid::orientations`
+  pub orientation: usize,
+  image: Image,
+}
+
+impl Placement {
+  /// The tile's pixels as placed, border included
+  pub fn pixels(&self) -> &Grid<bool> {
+    &self.image.pixels
+  }
+}
+
+/// The tiles arranged so every touching border matches
+#[derive(Clone, Debug, PartialEq)]
+pub struct Assembly {
+  tiles: Grid<Placement>,
+}
+
+impl Assembly {
+  /// The placed tiles, with the top left tile at `(0, 0)`
+  pub fn tiles(&self) -> &Grid<Placement> {
+    &self.tiles
+  }
+
+  /// IDs of the tiles in the corners of the picture
+  pub fn corners(&self) -> Vec<i64> {
+    let (right, bottom) = (self.tiles.width() - 1, self.tiles.height() - 1);
+    [(0, 0), (right, 0), (0, bottom), (right, bottom)]
+      .iter()
+      .copied()
+      .unique()
+      .map(|p| self.tiles[p].id)
+      .collect()
+  }
+
+  /// Join the tiles into one image, dropping each tile's border
+  pub fn image(&self) -> Grid<bool> {
+    let inner = match self.tiles.iter().next() {
+      Some(tile) => tile.pixels().width() - 2,
+      None => return Grid::new(0, 0, false),
+    };
+    Grid::from_fn(
+      self.tiles.width() * inner,
+      self.tiles.height() * inner,
+      |(x, y)| self.tiles[(x / inner, y / inner)].pixels()[(x % inner + 1, y % inner + 1)],
+    )
+  }
+
+  /// One line per row of tiles, giving each tile's ID and orientation.
+  /// The tile with the lowest ID keeps its orientation from the input, so
+  /// the whole picture may come out turned or flipped.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use advent_of_code_2020::day::day20::{assemble, parse};
+  ///
+  /// let tiles = parse(&r"Tile 1:
+  ///*****.
+  ///**..**
+  ///..*.*.
+  ///..****
+  ///.**...
+  ///*...*.
+  ///
+  ///Tile 2:
+  ///.*.*..
+  ///.**.**
+  ///*..*..
+  ///**.**.
+  ///**.*.*
+  ///.*.*.*
+  ///
+  ///Tile 3:
+  ///...*..
+  ///**.*.*
+  ///.*..*.
+  ///......
+  ///......
+  ///....**
+  ///
+  ///Tile 4:
+  ///.*..**
+  ///.***..
+  ///***..*
+  ///.**.**
+  ///.*..**
+  ///.*.**.".replace('*', "#")).unwrap();
+  /// assert_eq!(assemble(&tiles).unwrap().layout(), "1 r0    2 f90\n3 f270  4 r0\n");
+  /// ```
+  pub fn layout(&self) -> String {
+    let cells: Vec<String> = self
+      .tiles
+      .iter()
+      .map(|tile| format!("{} {}", tile.id, ORIENTATION_NAMES[tile.orientation]))
+      .collect();
+    let width = cells.iter().map(String::len).max().unwrap_or(0);
+    let mut out = String::new();
+    for row in cells.chunks(self.tiles.width().max(1)) {
+      let line = row
+        .iter()
+        .map(|cell| format!("{:<1$}", cell, width))
+        .join("  ");
+      out.push_str(line.trim_end());
+      out.push('\n');
+    }
+    out
+  }
+}
+
+/// Why the tiles couldn't be assembled
+#[derive(Clone, Debug, PartialEq)]
+pub enum AssemblyError {
+  /// No arrangement of the tiles has every touching border match
+  NoAssembly,
+  /// The tiles fit together in more than one way. These are two of them.
+  Ambiguous(Box<Assembly>, Box<Assembly>),
+}
+
+impl fmt::Display for AssemblyError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      AssemblyError::NoAssembly => write!(f, "the tiles don't fit together"),
+      AssemblyError::Ambiguous(..) => write!(f, "the tiles fit together in more than one way"),
+    }
+  }
+}
+
+impl std::error::Error for AssemblyError {}
+
+/// Arrange the tiles into a rectangle so that every pair of touching
+/// borders match. Borders are looked up by bitmask, and every arrangement
+/// is searched for, so a border may match more than one other tile as long
+/// as the tiles only fit together one way. Arrangements that are the same
+/// picture turned or flipped count as one.
+pub fn assemble(input: &Tiles) -> std::result::Result<Assembly, AssemblyError> {
+  let mut ids: Vec<i64> = input.keys().copied().collect();
+  ids.sort_unstable();
+  let pieces: Vec<Piece> = ids
+    .iter()
+    .map(|&id| {
+      let orientations: Vec<(Image, [u64; 4])> = input[&id]
+        .orientations()
+        .into_iter()
+        .map(|image| {
+          let borders = image.borders();
+          (image, borders)
+        })
+        .collect();
+      Piece { id, orientations }
+    })
+    .collect();
+  let size = pieces
+    .first()
+    .map_or(0, |piece| piece.orientations[0].0.pixels.width());
+
+  let mut index: HashMap<u64, Vec<usize>> = HashMap::new();
+  for (idx, piece) in pieces.iter().enumerate() {
+    for &border in &piece.orientations[0].1 {
+      let tiles = index.entry(canonical(border, size)).or_default();
+      if tiles.last() != Some(&idx) {
+        tiles.push(idx);
+      }
+    }
+  }
+
+  let mut search = Search {
+    pieces: &pieces,
+    index: &index,
+    size,
+    width: 0,
+    used: vec![false; pieces.len()],
+    placed: Vec::with_capacity(pieces.len()),
+    found: Vec::new(),
+  };
+  for width in (1..=pieces.len()).filter(|width| pieces.len() / width * width == pieces.len()) {
+    search.width = width;
+    if search.place() {
+      break;
+    }
+  }
+
+  let mut found = search.found.into_iter().map(|(_, assembly)| assembly);
+  match (found.next(), found.next()) {
+    (None, _) => Err(AssemblyError::NoAssembly),
+    (Some(assembly), None) => Ok(normalise(assembly, input, ids[0])),
+    (Some(first), Some(second)) => Err(AssemblyError::Ambiguous(
+      Box::new(normalise(first, input, ids[0])),
+      Box::new(normalise(second, input, ids[0])),
+    )),
+  }
+}
+
+/// A tile in each of its orientations, with their borders
+struct Piece {
+  id: i64,
+  orientations: Vec<(Image, [u64; 4])>,
+}
+
+/// The width and the row by row IDs and pixels of the tiles of an assembly
+type AssemblyKey = (usize, Vec<(i64, Vec<bool>)>);
+
+/// Placing tiles row by row into a rectangle `width` tiles wide
+struct Search<'a> {
+  pieces: &'a [Piece],
+  index: &'a HashMap<u64, Vec<usize>>,
+  size: usize,
+  width: usize,
+  used: Vec<bool>,
+  /// The piece and orientation in each filled slot
+  placed: Vec<(usize, usize)>,
+  /// Each different assembly found, keyed by its picture in a fixed orientation
+  found: Vec<(AssemblyKey, Assembly)>,
+}
+
+const TOP: usize = 0;
+const BOTTOM: usize = 1;
+const LEFT: usize = 2;
+const RIGHT: usize = 3;
+
+impl Search<'_> {
+  fn borders(&self, slot: usize) -> [u64; 4] {
+    let (piece, orientation) = self.placed[slot];
+    self.pieces[piece].orientations[orientation].1
+  }
+
+  /// Fill the next slot in every way that fits, returning true once a
+  /// second assembly has been found and there's no need to go on
+  fn place(&mut self) -> bool {
+    let slot = self.placed.len();
+    if slot == self.pieces.len() {
+      self.record();
+      return self.found.len() > 1;
+    }
+
+    let (x, y) = (slot % self.width, slot / self.width);
+    let left = (x > 0).then(|| self.borders(slot - 1)[RIGHT]);
+    let above = (y > 0).then(|| self.borders(slot - self.width)[BOTTOM]);
+    let candidates: Vec<usize> = match left.or(above) {
+      Some(border) => self
+        .index
+        .get(&canonical(border, self.size))
+        .cloned()
+        .unwrap_or_default(),
+      None => (0..self.pieces.len()).collect(),
+    };
+
+    for piece in candidates {
+      if self.used[piece] {
+        continue;
+      }
+      for (orientation, (_, borders)) in self.pieces[piece].orientations.iter().enumerate() {
+        if left.is_some_and(|border| borders[LEFT] != border)
+          || above.is_some_and(|border| borders[TOP] != border)
+        {
+          continue;
+        }
+        self.used[piece] = true;
+        self.placed.push((piece, orientation));
+        let done = self.place();
+        self.placed.pop();
+        self.used[piece] = false;
+        if done {
+          return true;
+        }
       }
     }
-    stitched.extend(rows);
+    false
   }
 
-  match stitched.len() {
-    24 => remove_monsters::<24>(stitched.try_into().unwrap()),
-    96 => remove_monsters::<96>(stitched.try_into().unwrap()),
-    _ => panic!("unsupported dimensions"),
+  /// Keep the filled rectangle unless it's one already found turned or flipped
+  fn record(&mut self) {
+    let height = self.pieces.len() / self.width;
+    let tiles = Grid::from_fn(self.width, height, |(x, y)| {
+      let (piece, orientation) = self.placed[y * self.width + x];
+      let piece = &self.pieces[piece];
+      Placement {
+        id: piece.id,
+        orientation,
+        image: piece.orientations[orientation].0.clone(),
+      }
+    });
+    let assembly = Assembly { tiles };
+    let key = (0..8)
+      .map(|turn| {
+        let turned = turned(&assembly, turn);
+        let tiles = turned.tiles.iter();
+        let tiles = tiles.map(|tile| (tile.id, tile.image.pixels.iter().copied().collect()));
+        (turned.tiles.width(), tiles.collect())
+      })
+      .min()
+      .unwrap();
+    if self.found.iter().all(|(found, _)| *found != key) {
+      self.found.push((key, assembly));
+    }
   }
 }
 
-fn assemble(
-  input: &HashMap<i64, Image<10>>,
-) -> (HashMap<(i64, i64), Image<10>>, HashMap<(i64, i64), i64>) {
-  let mut images = input.clone();
-  let mut img = HashMap::new();
-  let mut ids = HashMap::new();
-
-  let first_id = images.keys().copied().next().unwrap();
-  ids.insert((0, 0), first_id);
-
-  let first_tile = images.remove(&first_id).unwrap();
-  img.insert((0, 0), first_tile);
-
-  while images.len() > 0 {
-    let mut failed = HashMap::new();
-
-    'next_tile: for (id, image) in &images {
-      for piece in image.orientations() {
-        'next_pos: for (pos, o) in &img {
-          let (x, y) = *pos;
-          let (above, below, left, right) = ((x, y - 1), (x, y + 1), (x - 1, y), (x + 1, y));
-
-          let placement = if !img.contains_key(&above) && piece.fits_above(o) {
-            above
-          } else if !img.contains_key(&below) && piece.fits_below(o) {
-            below
-          } else if !img.contains_key(&left) && piece.fits_left(o) {
-            left
-          } else if !img.contains_key(&right) && piece.fits_right(o) {
-            right
-          } else {
-            continue 'next_pos;
-          };
-          ids.insert(placement, *id);
-          img.insert(placement, piece);
-          continue 'next_tile;
+/// The whole assembly in orientation `turn` of `Grid::orientations`
+fn turned(assembly: &Assembly, turn: usize) -> Assembly {
+  let tiles = assembly.tiles.orientation(turn);
+  let tiles = tiles.map(|tile| Placement {
+    id: tile.id,
+    orientation: tile.orientation,
+    image: Image {
+      pixels: tile.image.pixels.orientation(turn),
+    },
+  });
+  Assembly { tiles }
+}
+
+/// Turn the assembly so the tile `first` is as it was in the input, and
+/// give each tile the orientation that takes it there from the input
+fn normalise(assembly: Assembly, input: &Tiles, first: i64) -> Assembly {
+  let original = &input[&first];
+  let assembly = (0..8)
+    .map(|turn| turned(&assembly, turn))
+    .find(|turned| {
+      turned
+        .tiles
+        .iter()
+        .any(|tile| tile.id == first && tile.image == *original)
+    })
+    .expect("some turn leaves each tile as it was");
+  let tiles = assembly.tiles.map(|tile| Placement {
+    id: tile.id,
+    orientation: input[&tile.id]
+      .orientations()
+      .iter()
+      .position(|image| *image == tile.image)
+      .expect("placed tiles are orientations of the input"),
+    image: tile.image.clone(),
+  });
+  Assembly { tiles }
+}
+
+/// The sea monster from part 2, as a template for `Pattern::parse`
+pub const SEA_MONSTER: &str = "                  # \n#    ##    ##    ###\n #  #  #  #  #  #   ";
+
+/// A shape to look for in an image, made of the pixels that must be set
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pattern {
+  pixels: Grid<bool>,
+}
+
+impl Pattern {
+  /// Parse a text template where `#` is part of the pattern and `.` or a
+  /// space is not. Shorter rows are padded out to the longest.
+  pub fn parse(template: &str) -> Result<Self> {
+    let width = template.lines().map(|row| row.chars().count()).max();
+    let mut rows = Vec::new();
+    for row in template.lines() {
+      let mut cells = Vec::with_capacity(width.unwrap_or(0));
+      for (idx, c) in row.char_indices() {
+        match c {
+          '#' => cells.push(true),
+          '.' | ' ' => cells.push(false),
+          _ => {
+            return Err(Error::at(
+              template,
+              &row[idx..idx + c.len_utf8()],
+              "expected `#`, `.` or a space",
+            ))
+          }
         }
       }
-      failed.insert(*id, *image);
+      cells.resize(width.unwrap_or(0), false);
+      rows.push(cells);
+    }
+    let pixels = Grid::from_rows(rows).expect("rows were padded to the same length");
+    if !pixels.iter().any(|&set| set) {
+      return Err(Error::at(
+        template,
+        template,
+        "expected a pattern with a `#`",
+      ));
     }
+    Ok(Self { pixels })
+  }
 
-    images = failed;
+  pub fn width(&self) -> usize {
+    self.pixels.width()
   }
 
-  (img, ids)
+  pub fn height(&self) -> usize {
+    self.pixels.height()
+  }
+
+  /// Offsets of the pixels that must be set
+  pub fn cells(&self) -> impl Iterator<Item = Position> + '_ {
+    self.pixels.positions().filter(move |&p| self.pixels[p])
+  }
+
+  /// The pattern in each of the 8 orientations of `Grid::orientations`
+  pub fn orientations(&self) -> Vec<Self> {
+    self
+      .pixels
+      .orientations()
+      .into_iter()
+      .map(|pixels| Self { pixels })
+      .collect()
+  }
 }
 
-fn row_match(pixels: &[bool], monster: &[u8]) -> bool {
-  pixels
+/// Where a pattern was found in an image
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Match {
+  /// Index of the pattern orientation that matched, as in `Pattern::orientations`
+  pub orientation: usize,
+  /// The top left corner of the oriented pattern in the image
+  pub position: Position,
+  /// The image pixels the match covers
+  pub pixels: Vec<Position>,
+}
+
+/// Find every place `pattern` appears in `image`, in any orientation.
+/// Matches may overlap, and a symmetric pattern is only reported once for
+/// each set of pixels.
+///
+/// # Example
+///
+/// ```
+/// use advent_of_code_2020::day::day20::{find_pattern, highlight, roughness, Pattern};
+/// use advent_of_code_2020::grid::Grid;
+///
+/// let image = Grid::parse(".....\n.##..\n..##.\n.....\n.##..", |c| Some(c == '#')).unwrap();
+/// let pattern = Pattern::parse("##").unwrap();
+/// let matches = find_pattern(&image, &pattern);
+/// let positions: Vec<_> = matches.iter().map(|m| m.position).collect();
+/// assert_eq!(positions, vec![(1, 1), (2, 2), (1, 4), (2, 1)]);
+/// assert_eq!(roughness(&image, &matches), 0);
+///
+/// // The two matches of an L shape share a pixel, which only counts once
+/// let corner = Pattern::parse("#.\n##").unwrap();
+/// let matches = find_pattern(&image, &corner);
+/// assert_eq!(matches.len(), 2);
+/// assert_eq!(roughness(&image, &matches), 2);
+/// assert_eq!(highlight(&image, &matches), ".....\n.OO..\n..OO.\n.....\n.##..\n");
+/// ```
+pub fn find_pattern(image: &Grid<bool>, pattern: &Pattern) -> Vec<Match> {
+  let mut seen = HashSet::new();
+  let mut matches = Vec::new();
+  for (orientation, oriented) in pattern.orientations().iter().enumerate() {
+    for found in find_in_orientation(image, oriented, orientation) {
+      let mut key = found.pixels.clone();
+      key.sort_unstable();
+      if seen.insert(key) {
+        matches.push(found);
+      }
+    }
+  }
+  matches
+}
+
+/// Find `pattern` in the one orientation it appears in most, since only one
+/// orientation of the image is the right way round. Ties go to the earlier
+/// orientation.
+///
+/// # Example
+///
+/// ```
+/// use advent_of_code_2020::day::day20::{find_oriented, find_pattern, roughness, Pattern};
+/// use advent_of_code_2020::grid::Grid;
+///
+/// let image = Grid::parse("##...\n#....\n.....\n##.##\n.#.#.", |c| Some(c == '#')).unwrap();
+/// let corner = Pattern::parse("##\n#.").unwrap();
+/// let matches = find_oriented(&image, &corner);
+/// assert_eq!(matches.len(), 2);
+/// assert!(matches.iter().all(|m| m.orientation == matches[0].orientation));
+/// assert_eq!(roughness(&image, &matches), 3);
+/// // Every orientation at once covers the mirrored corner too
+/// assert_eq!(roughness(&image, &find_pattern(&image, &corner)), 0);
+/// ```
+pub fn find_oriented(image: &Grid<bool>, pattern: &Pattern) -> Vec<Match> {
+  let mut best: Vec<Match> = Vec::new();
+  for (orientation, oriented) in pattern.orientations().iter().enumerate() {
+    let matches = find_in_orientation(image, oriented, orientation);
+    if matches.len() > best.len() {
+      best = matches;
+    }
+  }
+  best
+}
+
+/// Every place an already oriented pattern appears in `image`
+fn find_in_orientation(image: &Grid<bool>, oriented: &Pattern, orientation: usize) -> Vec<Match> {
+  if image.width() < oriented.width() || image.height() < oriented.height() {
+    return Vec::new();
+  }
+  let cells: Vec<Position> = oriented.cells().collect();
+  let mut matches = Vec::new();
+  for y in 0..=image.height() - oriented.height() {
+    for x in 0..=image.width() - oriented.width() {
+      let pixels: Vec<Position> = cells.iter().map(|&(dx, dy)| (x + dx, y + dy)).collect();
+      if pixels.iter().all(|&p| image[p]) {
+        matches.push(Match {
+          orientation,
+          position: (x, y),
+          pixels,
+        });
+      }
+    }
+  }
+  matches
+}
+
+/// How many set pixels aren't part of any match
+pub fn roughness(image: &Grid<bool>, matches: &[Match]) -> usize {
+  let covered: HashSet<Position> = matches
     .iter()
-    .zip(monster.iter())
-    .all(|(v, c)| *c == b' ' || *v)
+    .flat_map(|m| m.pixels.iter().copied())
+    .collect();
+  image.iter().filter(|&&set| set).count() - covered.len()
 }
 
-fn remove_monsters<const N: usize>(img: Image<N>) -> usize {
-  let num_hash = img
-    .pixels
+/// Render the image with `O` for pixels in a match, `#` for other set
+/// pixels and `.` for the rest
+pub fn highlight(image: &Grid<bool>, matches: &[Match]) -> String {
+  let covered: HashSet<Position> = matches
     .iter()
-    .flat_map(|row| row.iter())
-    .filter(|x| **x)
-    .count();
-
-  let monster = [
-    b"                  # ",
-    b"#    ##    ##    ###",
-    b" #  #  #  #  #  #   ",
-  ];
-
-  for img in img.orientations() {
-    let mut monster_count = 0;
-    for rows in img.pixels.windows(3) {
-      let (t, m, b) = match rows {
-        [t, m, b] => (t, m, b),
-        _ => unreachable!(),
-      };
-
-      let w = monster[0].len();
-      for (t, m, b) in izip!(t.windows(w), m.windows(w), b.windows(w)) {
-        if izip!(&[t, m, b], &monster).all(|(p, m)| row_match(p, *m)) {
-          monster_count += 1;
-        }
-      }
+    .flat_map(|m| m.pixels.iter().copied())
+    .collect();
+  let marked = Grid::from_fn(image.width(), image.height(), |p| {
+    if covered.contains(&p) {
+      'O'
+    } else if image[p] {
+      '#'
+    } else {
+      '.'
     }
+  });
+  marked.render(|&c| c)
+}
+
+/// Plain PBM and PPM files keep their lines to 70 characters
+const NETPBM_LINE: usize = 70;
 
-    if monster_count != 0 {
-      return num_hash - 15 * monster_count;
+/// The image as a plain PBM (`P1`) bitmap, with set pixels black
+///
+/// # Example
+///
+/// ```
+/// use advent_of_code_2020::day::day20::to_pbm;
+/// use advent_of_code_2020::grid::Grid;
+///
+/// let image = Grid::parse(".#.\n##.", |c| Some(c == '#')).unwrap();
+/// assert_eq!(to_pbm(&image), "P1\n3 2\n010\n110\n");
+/// ```
+pub fn to_pbm(image: &Grid<bool>) -> String {
+  let mut out = format!("P1\n{} {}\n", image.width(), image.height());
+  for row in image.rows() {
+    for chunk in row.chunks(NETPBM_LINE) {
+      out.extend(chunk.iter().map(|&set| if set { '1' } else { '0' }));
+      out.push('\n');
     }
   }
+  out
+}
 
-  println!("no monsters found");
-  num_hash
+/// The image as a plain PPM (`P3`) pixmap: deep water for empty pixels,
+/// foam for rough water and green for pixels in a match
+pub fn to_ppm(image: &Grid<bool>, matches: &[Match]) -> String {
+  let covered: HashSet<Position> = matches
+    .iter()
+    .flat_map(|m| m.pixels.iter().copied())
+    .collect();
+  let colours = image.positions().map(|p| {
+    if covered.contains(&p) {
+      "40 200 60"
+    } else if image[p] {
+      "200 220 255"
+    } else {
+      "10 40 120"
+    }
+  });
+  let mut out = format!("P3\n{} {}\n255\n", image.width(), image.height());
+  // At most 11 characters a pixel, so 5 to a line with spaces between
+  for mut line in &colours.chunks(5) {
+    out.push_str(&line.join(" "));
+    out.push('\n');
+  }
+  out
 }
@@ -1,94 +1,84 @@
-use std::collections::{HashMap, HashSet};
+use itertools::Itertools;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
 
-/// Food ingredients and alergens.
-/// Alergens may not always be listed.
-/// Find the ingredients that do not contain listed alergens,
+use crate::assignment::{self, AssignmentError};
+use crate::{Error, Result, Solution};
+
+pub struct Day21;
+
+impl Solution for Day21 {
+  type Input = Labelled;
+  type Part1 = usize;
+  type Part2 = String;
+
+  fn parse(input: &str) -> Result<Labelled> {
+    label(input)
+  }
+
+  fn part1(input: &Labelled) -> usize {
+    part1(&input.foods)
+  }
+
+  fn part2(input: &Labelled) -> String {
+    part2(input)
+  }
+}
+
+/// Food ingredients and allergens.
+/// Allergens may not always be listed.
+/// Find the ingredients that do not contain listed allergens,
 /// then count the number of times those ingredients appear
 ///
 /// # Example
 /// ```
 /// use advent_of_code_2020::day::day21::*;
 ///
-/// let input = "mxmxvkd kfcds sqjhc nhms (contains dairy, fish)
+/// let input = parse("mxmxvkd kfcds sqjhc nhms (contains dairy, fish)
 ///trh fvjkl sbzzf mxmxvkd (contains dairy)
 ///sqjhc fvjkl (contains soy)
-///sqjhc mxmxvkd sbzzf (contains fish)".to_string();
+///sqjhc mxmxvkd sbzzf (contains fish)").unwrap();
 /// let results = part1(&input);
 /// assert_eq!(results, 5);
 /// ```
-pub fn part1(i: &String) -> usize {
-  let foods = parse(i);
-  let all_ingredients_vec: Vec<String> = foods.iter().flat_map(|f| f.ingredients.clone()).collect();
-  let all_ingredients_set: HashSet<String> = all_ingredients_vec.iter().cloned().collect();
-  let by_alergen = ingredient_by_alergen(&foods);
-  let alergen_candidates: HashSet<String> = by_alergen.values().cloned().flatten().collect();
-  all_ingredients_set
-    .difference(&alergen_candidates)
-    .fold(0, |count, ingredient| {
-      count
-        + all_ingredients_vec
-          .iter()
-          .filter(|s| *s == ingredient)
-          .count()
-    })
+pub fn part1(foods: &[Food]) -> usize {
+  analyse(foods).safe.values().sum()
 }
 
 #[derive(Clone, Debug)]
-struct Food {
+pub struct Food {
   ingredients: Vec<String>,
-  alergens: Vec<String>,
+  allergens: Vec<String>,
 }
 
-fn parse(i: &String) -> Vec<Food> {
-  i.split("\n")
+/// Parse one `ingredients... (contains allergens...)` food per line
+pub fn parse(i: &str) -> Result<Vec<Food>> {
+  i.lines()
+    .filter(|line| !line.is_empty())
     .map(|line| {
-      let parts: Vec<&str> = line.split("(").collect();
-      let ingredients = parts[0].trim().split(" ").map(|s| s.to_string()).collect();
-      let alergens = if parts.len() > 1 {
-        parts[1]
-          .replace(")", "")
-          .replace("contains", "")
-          .trim()
+      let (raw_ingredients, raw_allergens) = match line.split_once(" (") {
+        Some((ingredients, allergens)) => (ingredients, Some(allergens)),
+        None => (line, None),
+      };
+      let ingredients = raw_ingredients.split(' ').map(|s| s.to_string()).collect();
+      let allergens = match raw_allergens {
+        Some(raw) => raw
+          .strip_prefix("contains ")
+          .and_then(|raw| raw.strip_suffix(')'))
+          .ok_or_else(|| Error::at(i, raw, "expected `(contains <allergens>)`"))?
           .split(", ")
           .map(|s| s.to_string())
-          .collect()
-      } else {
-        vec![]
+          .collect(),
+        None => vec![],
       };
-      Food {
+      Ok(Food {
         ingredients,
-        alergens,
-      }
+        allergens,
+      })
     })
     .collect()
 }
 
-fn ingredient_by_alergen(foods: &Vec<Food>) -> HashMap<String, HashSet<String>> {
-  let mut results: HashMap<String, HashSet<String>> = HashMap::new();
-  foods.iter().cloned().for_each(|food| {
-    let current_ingredients: HashSet<String> = food.ingredients.iter().cloned().collect();
-    food
-      .alergens
-      .iter()
-      .cloned()
-      .for_each(|alergen| match results.clone().get(&alergen) {
-        Some(ingredients) => {
-          results.insert(
-            alergen,
-            ingredients
-              .intersection(&current_ingredients)
-              .map(|s| s.to_string())
-              .collect(),
-          );
-        }
-        None => {
-          results.insert(alergen, current_ingredients.clone());
-        }
-      });
-  });
-  results
-}
-
 /// Now that you've isolated the inert ingredients, you should have enough information to figure out which ingredient contains which allergen.
 /// Arrange the ingredients alphabetically by their allergen and separate them by commas to produce your canonical dangerous ingredient list. (There should not be any spaces in your canonical dangerous ingredient list.) In the above example, this would be mxmxvkd,sqjhc,fvjkl.
 ///
@@ -96,44 +86,327 @@ fn ingredient_by_alergen(foods: &Vec<Food>) -> HashMap<String, HashSet<String>>
 /// ```
 /// use advent_of_code_2020::day::day21::*;
 ///
-/// let input = "mxmxvkd kfcds sqjhc nhms (contains dairy, fish)
+/// let input = label("mxmxvkd kfcds sqjhc nhms (contains dairy, fish)
 ///trh fvjkl sbzzf mxmxvkd (contains dairy)
 ///sqjhc fvjkl (contains soy)
-///sqjhc mxmxvkd sbzzf (contains fish)".to_string();
+///sqjhc mxmxvkd sbzzf (contains fish)").unwrap();
 /// let results = part2(&input);
 /// assert_eq!(results, "mxmxvkd,sqjhc,fvjkl");
 /// ```
-pub fn part2(i: &String) -> String {
-  let foods = parse(i);
-  let by_alergen = ingredient_by_alergen(&foods);
-  let mut alergens: Vec<&String> = by_alergen.keys().collect();
-  alergens.sort();
-  let alergen_map = narrow_candidates(&by_alergen);
-  alergens
+pub fn part2(labelled: &Labelled) -> String {
+  labelled.mapping.values().join(",")
+}
+
+/// The foods with the ingredient each allergen is in
+#[derive(Clone, Debug)]
+pub struct Labelled {
+  foods: Vec<Food>,
+  mapping: BTreeMap<String, String>,
+}
+
+impl Labelled {
+  pub fn foods(&self) -> &[Food] {
+    &self.foods
+  }
+
+  /// The ingredient holding each allergen
+  pub fn mapping(&self) -> &BTreeMap<String, String> {
+    &self.mapping
+  }
+}
+
+/// Parse the foods and work out which ingredient holds each allergen,
+/// pointing at the first listing of an allergen that no ingredient is left
+/// for or that more than one ingredient fits.
+///
+/// # Example
+///
+/// ```
+/// use advent_of_code_2020::day::day21::*;
+///
+/// let labelled = label("a b (contains dairy)\na c (contains dairy, soy)\nc (contains soy)").unwrap();
+/// assert_eq!(labelled.mapping()["soy"], "c");
+///
+/// let err = label("a b (contains dairy)\nc d (contains dairy, soy)").unwrap_err();
+/// assert_eq!(
+///   err.to_string(),
+///   "no ingredient is left for this allergen at line 1, column 15: \"dairy\""
+/// );
+/// let err = label("a b (contains dairy, soy)\na b (contains soy)").unwrap_err();
+/// assert_eq!(
+///   err.to_string(),
+///   "allergen could be in a or b at line 1, column 15: \"dairy\""
+/// );
+/// ```
+pub fn label(i: &str) -> Result<Labelled> {
+  let foods = parse(i)?;
+  let mapping = analyse(&foods).mapping.map_err(|err| {
+    let (allergen, message) = match err {
+      MappingError::Unmatched(allergens) => (
+        allergens[0].clone(),
+        "no ingredient is left for this allergen".to_string(),
+      ),
+      MappingError::Ambiguous(first, second) => {
+        let (allergen, ingredient) = first
+          .iter()
+          .find(|(allergen, ingredient)| second[*allergen] != **ingredient)
+          .expect("two different mappings differ somewhere");
+        let other = &second[allergen];
+        let message = format!(
+          "allergen could be in {} or {}",
+          ingredient.min(other),
+          ingredient.max(other)
+        );
+        (allergen.clone(), message)
+      }
+    };
+    Error::at(i, first_listing(i, &allergen), message)
+  })?;
+  Ok(Labelled { foods, mapping })
+}
+
+/// Where `allergen` is first listed in the foods
+fn first_listing<'a>(i: &'a str, allergen: &str) -> &'a str {
+  i.lines()
+    .filter_map(|line| line.split_once(" (contains "))
+    .flat_map(|(_, listed)| listed.trim_end_matches(')').split(", "))
+    .find(|&listed| listed == allergen)
+    .unwrap_or(&i[i.len()..])
+}
+
+/// The ingredients each allergen could be in
+pub type Candidates = BTreeMap<String, BTreeSet<String>>;
+
+/// One round of elimination: an allergen down to a single candidate is
+/// settled, and that ingredient is crossed off for the others
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Step {
+  pub allergen: String,
+  pub ingredient: String,
+  /// The candidates of the allergens still unsettled afterwards
+  pub remaining: Candidates,
+}
+
+/// Why the allergens couldn't each be given one ingredient
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MappingError {
+  /// However the ingredients are chosen, these allergens are left without one
+  Unmatched(Vec<String>),
+  /// More than one mapping fits the foods. These are two of them.
+  Ambiguous(BTreeMap<String, String>, BTreeMap<String, String>),
+}
+
+impl fmt::Display for MappingError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      MappingError::Unmatched(allergens) => write!(
+        f,
+        "no mapping leaves an ingredient for {}",
+        allergens.join(", ")
+      ),
+      MappingError::Ambiguous(first, second) => write!(
+        f,
+        "more than one mapping fits, such as {} or {}",
+        describe(first),
+        describe(second)
+      ),
+    }
+  }
+}
+
+impl std::error::Error for MappingError {}
+
+fn describe(mapping: &BTreeMap<String, String>) -> String {
+  mapping
+    .iter()
+    .map(|(allergen, ingredient)| format!("{}={}", allergen, ingredient))
+    .join(", ")
+}
+
+/// Everything worked out about which ingredients hold which allergens
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Analysis {
+  /// For each allergen, the ingredients in every food that lists it
+  pub candidates: Candidates,
+  /// The ingredients that can't hold any allergen, and how many times
+  /// each appears in the foods
+  pub safe: BTreeMap<String, usize>,
+  /// Each round of eliminating single candidates, in order. These stop
+  /// early if no allergen is ever down to one candidate.
+  pub steps: Vec<Step>,
+  /// The ingredient holding each allergen
+  pub mapping: std::result::Result<BTreeMap<String, String>, MappingError>,
+}
+
+/// Work out which ingredients can hold each allergen, then settle them by
+/// elimination.
+///
+/// # Example
+///
+/// ```
+/// use advent_of_code_2020::day::day21::*;
+///
+/// let foods = parse("mxmxvkd kfcds sqjhc nhms (contains dairy, fish)
+///trh fvjkl sbzzf mxmxvkd (contains dairy)
+///sqjhc fvjkl (contains soy)
+///sqjhc mxmxvkd sbzzf (contains fish)").unwrap();
+/// let analysis = analyse(&foods);
+/// assert_eq!(analysis.safe.keys().collect::<Vec<_>>(), vec!["kfcds", "nhms", "sbzzf", "trh"]);
+/// assert_eq!(analysis.steps[0].allergen, "dairy");
+/// assert_eq!(analysis.steps[0].remaining["fish"].len(), 1);
+/// assert_eq!(analysis.mapping.as_ref().unwrap()["soy"], "fvjkl");
+/// assert!(analysis
+///   .explain()
+///   .contains("Step 1: dairy can only be in mxmxvkd, so no other allergen is\n"));
+///
+/// // Nothing is in both foods that list dairy
+/// let foods = parse("a b (contains dairy)\nc d (contains dairy, soy)").unwrap();
+/// let analysis = analyse(&foods);
+/// assert_eq!(
+///   analysis.mapping,
+///   Err(MappingError::Unmatched(vec!["dairy".to_string()]))
+/// );
+/// assert!(analysis.explain().contains("dairy: nothing"));
+/// ```
+pub fn analyse(foods: &[Food]) -> Analysis {
+  let mut candidates = Candidates::new();
+  for food in foods {
+    let ingredients: BTreeSet<String> = food.ingredients.iter().cloned().collect();
+    for allergen in &food.allergens {
+      let entry = candidates
+        .entry(allergen.clone())
+        .or_insert_with(|| ingredients.clone());
+      entry.retain(|ingredient| ingredients.contains(ingredient));
+    }
+  }
+
+  let unsafe_ingredients: BTreeSet<&String> = candidates.values().flatten().collect();
+  let mut safe = BTreeMap::new();
+  for ingredient in foods.iter().flat_map(|food| &food.ingredients) {
+    if !unsafe_ingredients.contains(ingredient) {
+      *safe.entry(ingredient.clone()).or_insert(0) += 1;
+    }
+  }
+
+  Analysis {
+    steps: eliminate(&candidates),
+    mapping: map_allergens(&candidates),
+    candidates,
+    safe,
+  }
+}
+
+/// Repeatedly settle the first allergen with a single candidate
+fn eliminate(candidates: &Candidates) -> Vec<Step> {
+  let mut remaining = candidates.clone();
+  let mut steps = Vec::new();
+  while let Some((allergen, ingredient)) = remaining
     .iter()
-    .cloned()
-    .filter_map(|alergen| alergen_map.get(alergen))
-    .cloned()
-    .collect::<Vec<String>>()
-    .join(",")
-}
-
-fn narrow_candidates(candidates: &HashMap<String, HashSet<String>>) -> HashMap<String, String> {
-  let mut pending = candidates.clone();
-  let mut results: HashMap<String, String> = HashMap::new();
-  loop {
-    if pending.iter().all(|(_, c)| c.is_empty()) {
-      break;
+    .find(|(_, ingredients)| ingredients.len() == 1)
+    .map(|(allergen, ingredients)| (allergen.clone(), ingredients.iter().next().unwrap().clone()))
+  {
+    remaining.remove(&allergen);
+    for ingredients in remaining.values_mut() {
+      ingredients.remove(&ingredient);
     }
-    pending.clone().iter().for_each(|(alergen, ingredients)| {
-      if ingredients.len() == 1 {
-        let locked_ingredient = ingredients.iter().next().unwrap();
-        results.insert(alergen.to_string(), locked_ingredient.to_string());
-        for (_, val) in pending.iter_mut() {
-          val.remove(locked_ingredient);
-        }
-      }
+    steps.push(Step {
+      allergen,
+      ingredient,
+      remaining: remaining.clone(),
     });
   }
-  results
+  steps
+}
+
+/// The one ingredient each allergen is in
+fn map_allergens(
+  candidates: &Candidates,
+) -> std::result::Result<BTreeMap<String, String>, MappingError> {
+  let allergens: Vec<&String> = candidates.keys().collect();
+  let ingredients: Vec<&String> = candidates.values().flatten().sorted().dedup().collect();
+  let options: Vec<Vec<usize>> = candidates
+    .values()
+    .map(|options| {
+      options
+        .iter()
+        .map(|ingredient| ingredients.binary_search(&ingredient).unwrap())
+        .collect()
+    })
+    .collect();
+  let named = |chosen: Vec<usize>| {
+    allergens
+      .iter()
+      .zip(chosen)
+      .map(|(&allergen, ingredient)| (allergen.clone(), ingredients[ingredient].clone()))
+      .collect()
+  };
+  assignment::assign(&options)
+    .map(named)
+    .map_err(|err| match err {
+      AssignmentError::NoAssignment(unmatched) => MappingError::Unmatched(
+        unmatched
+          .into_iter()
+          .map(|allergen| allergens[allergen].clone())
+          .collect(),
+      ),
+      AssignmentError::Ambiguous(first, second) => {
+        MappingError::Ambiguous(named(first), named(second))
+      }
+    })
+}
+
+impl Analysis {
+  /// Describe how the mapping was found, one fact per line
+  pub fn explain(&self) -> String {
+    let mut out = String::from("Ingredients in every food listing each allergen:\n");
+    for (allergen, ingredients) in &self.candidates {
+      out.push_str(&format!(
+        "  {}\n",
+        describe_candidates(allergen, ingredients)
+      ));
+    }
+    out.push_str(&format!(
+      "Safe ingredients: {}\n",
+      if self.safe.is_empty() {
+        "none".to_string()
+      } else {
+        self.safe.keys().join(", ")
+      }
+    ));
+    for (idx, step) in self.steps.iter().enumerate() {
+      out.push_str(&format!(
+        "Step {}: {} can only be in {}, so no other allergen is\n",
+        idx + 1,
+        step.allergen,
+        step.ingredient
+      ));
+      for (allergen, ingredients) in &step.remaining {
+        out.push_str(&format!(
+          "  {}\n",
+          describe_candidates(allergen, ingredients)
+        ));
+      }
+    }
+    let settled = self.steps.len();
+    if settled < self.candidates.len() {
+      out.push_str(&format!(
+        "Elimination stops with {} of {} allergens settled, as none has a single candidate left\n",
+        settled,
+        self.candidates.len()
+      ));
+    }
+    match &self.mapping {
+      Ok(mapping) => out.push_str(&format!("Mapping: {}\n", describe(mapping))),
+      Err(err) => out.push_str(&format!("No mapping: {}\n", err)),
+    }
+    out
+  }
+}
+
+fn describe_candidates(allergen: &str, ingredients: &BTreeSet<String>) -> String {
+  if ingredients.is_empty() {
+    format!("{}: nothing", allergen)
+  } else {
+    format!("{}: {}", allergen, ingredients.iter().join(", "))
+  }
 }
@@ -1,3 +1,25 @@
+use crate::{Error, Result, Solution};
+
+pub struct Day22;
+
+impl Solution for Day22 {
+  type Input = Vec<Vec<usize>>;
+  type Part1 = usize;
+  type Part2 = usize;
+
+  fn parse(input: &str) -> Result<Vec<Vec<usize>>> {
+    parse(input)
+  }
+
+  fn part1(input: &Vec<Vec<usize>>) -> usize {
+    part1(input)
+  }
+
+  fn part2(input: &Vec<Vec<usize>>) -> usize {
+    part2(input)
+  }
+}
+
 /// Basically the card game of war.
 /// The winner players card goes on top of the losing players card then placed
 /// at the bottom of the winners deck.
@@ -9,7 +31,7 @@
 /// ```
 /// use advent_of_code_2020::day::day22::*;
 ///
-/// let input = "Player 1:
+/// let input = parse("Player 1:
 ///9
 ///2
 ///6
@@ -21,12 +43,12 @@
 ///8
 ///4
 ///7
-///10".to_string();
+///10").unwrap();
 /// let results = part1(&input);
 /// assert_eq!(results, 306);
 /// ```
-pub fn part1(i: &String) -> usize {
-  let mut players = parse(i);
+pub fn part1(decks: &[Vec<usize>]) -> usize {
+  let mut players = decks.to_vec();
   loop {
     if players[0].is_empty() || players[1].is_empty() {
       break;
@@ -37,18 +59,33 @@ pub fn part1(i: &String) -> usize {
   score(winner)
 }
 
-fn parse(i: &String) -> Vec<Vec<usize>> {
-  i.split("\n\n")
+/// Parse each player's deck, top card first
+pub fn parse(i: &str) -> Result<Vec<Vec<usize>>> {
+  let decks = i
+    .trim()
+    .split("\n\n")
     .map(|player| {
       player
-        .split("\n")
-        .filter_map(|line| line.parse().ok())
+        .lines()
+        .skip(1)
+        .map(|line| {
+          line
+            .parse()
+            .map_err(|_| Error::at(i, line, "expected a card"))
+        })
         .collect()
     })
-    .collect()
+    .collect::<Result<Vec<Vec<usize>>>>()?;
+  if decks.len() != 2 {
+    return Err(Error::at(i, i, "expected decks for two players"));
+  }
+  if decks.iter().all(|deck| deck.is_empty()) {
+    return Err(Error::at(i, i.trim(), "expected at least one card to play"));
+  }
+  Ok(decks)
 }
 
-fn play_round(players: &mut Vec<Vec<usize>>) {
+fn play_round(players: &mut [Vec<usize>]) {
   let player_one_card = players[0].remove(0);
   let player_two_card = players[1].remove(0);
   if player_one_card > player_two_card {
@@ -60,7 +97,7 @@ fn play_round(players: &mut Vec<Vec<usize>>) {
   }
 }
 
-fn score(deck: &Vec<usize>) -> usize {
+fn score(deck: &[usize]) -> usize {
   deck
     .iter()
     .rev()
@@ -80,7 +117,7 @@ fn score(deck: &Vec<usize>) -> usize {
 /// ```
 /// use advent_of_code_2020::day::day22::*;
 ///
-/// let input = "Player 1:
+/// let input = parse("Player 1:
 ///9
 ///2
 ///6
@@ -92,17 +129,17 @@ fn score(deck: &Vec<usize>) -> usize {
 ///8
 ///4
 ///7
-///10".to_string();
+///10").unwrap();
 /// let results = part2(&input);
 /// assert_eq!(results, 291);
 /// ```
-pub fn part2(i: &String) -> usize {
-  let mut players = parse(i);
+pub fn part2(decks: &[Vec<usize>]) -> usize {
+  let mut players = decks.to_vec();
   let winner = play_game(&mut players);
   score(&players[winner])
 }
 
-fn play_game(players: &mut Vec<Vec<usize>>) -> usize {
+fn play_game(players: &mut [Vec<usize>]) -> usize {
   let mut states: Vec<Vec<Vec<usize>>> = vec![vec![], vec![]];
   let mut winner = 0;
   loop {
@@ -133,14 +170,12 @@ fn play_game(players: &mut Vec<Vec<usize>>) -> usize {
         players[1].push(player_two_card);
         players[1].push(player_one_card);
       }
+    } else if player_one_card > player_two_card {
+      players[0].push(player_one_card);
+      players[0].push(player_two_card);
     } else {
-      if player_one_card > player_two_card {
-        players[0].push(player_one_card);
-        players[0].push(player_two_card);
-      } else {
-        players[1].push(player_two_card);
-        players[1].push(player_one_card);
-      }
+      players[1].push(player_two_card);
+      players[1].push(player_one_card);
     }
   }
   winner
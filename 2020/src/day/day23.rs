@@ -1,3 +1,25 @@
+use crate::{Error, Result, Solution};
+
+pub struct Day23;
+
+impl Solution for Day23 {
+  type Input = Vec<usize>;
+  type Part1 = String;
+  type Part2 = usize;
+
+  fn parse(input: &str) -> Result<Vec<usize>> {
+    parse(input)
+  }
+
+  fn part1(input: &Vec<usize>) -> String {
+    part1(input, 100)
+  }
+
+  fn part2(input: &Vec<usize>) -> usize {
+    part2(input, 10_000_000)
+  }
+}
+
 /// Cup game.
 ///
 /// Round steps:
@@ -12,12 +34,12 @@
 ///
 /// ```
 /// use advent_of_code_2020::day::day23::*;
-/// let input = "389125467".to_string();
-/// let results = part1(&input, 10);
-/// assert_eq!(results, "67384529");
+/// let input = parse("389125467").unwrap();
+/// assert_eq!(part1(&input, 10), "92658374");
+/// assert_eq!(part1(&input, 100), "67384529");
 /// ```
-pub fn part1(i: &String, num_moves: usize) -> String {
-  let mut cups = parse(i);
+pub fn part1(cups: &[usize], num_moves: usize) -> String {
+  let mut cups = cups.to_vec();
   for _ in 0..num_moves {
     play_round(&mut cups);
   }
@@ -37,10 +59,35 @@ pub fn part1(i: &String, num_moves: usize) -> String {
     .join("")
 }
 
-fn parse(i: &String) -> Vec<usize> {
-  i.chars()
-    .filter_map(|c| c.to_string().parse().ok())
-    .collect()
+/// Parse the cup labels, one digit per cup. The labels must be 1 to the
+/// number of cups, each used once, and there must be at least 5 cups so a
+/// move has somewhere to put the three it picks up.
+pub fn parse(i: &str) -> Result<Vec<usize>> {
+  let labels = i.trim_end();
+  let mut cups = Vec::new();
+  for (idx, c) in labels.char_indices() {
+    let label = &labels[idx..idx + c.len_utf8()];
+    match c.to_digit(10) {
+      Some(d) if d > 0 && !cups.contains(&(d as usize)) => cups.push(d as usize),
+      Some(d) if d > 0 => return Err(Error::at(i, label, "cup labels must be distinct")),
+      _ => return Err(Error::at(i, label, "expected a cup label `1`-`9`")),
+    }
+  }
+  if cups.len() < 5 {
+    return Err(Error::at(i, labels, "expected at least 5 cups"));
+  }
+  if let Some(missing) = (1..=cups.len()).find(|label| !cups.contains(label)) {
+    return Err(Error::at(
+      i,
+      labels,
+      format!(
+        "cup labels must be 1 to {}, missing {}",
+        cups.len(),
+        missing
+      ),
+    ));
+  }
+  Ok(cups)
 }
 
 fn play_round(cups: &mut Vec<usize>) {
@@ -64,8 +111,8 @@ fn play_round(cups: &mut Vec<usize>) {
     .enumerate()
     .find(|(_, v)| v == &destination_val)
     .unwrap();
-  for i in 0..3 {
-    cups.insert((destination_idx + i + 1) % cup_count, removed[i])
+  for (i, cup) in removed.into_iter().enumerate() {
+    cups.insert((destination_idx + i + 1) % cup_count, cup)
   }
   cups.rotate_left(1);
 }
@@ -77,12 +124,12 @@ fn play_round(cups: &mut Vec<usize>) {
 ///
 /// ```
 /// use advent_of_code_2020::day::day23::*;
-/// let input = "389125467".to_string();
+/// let input = parse("389125467").unwrap();
 /// let results = part2(&input, 10_000_000);
 /// assert_eq!(results, 149245887792);
 /// ```
-pub fn part2(i: &String, num_moves: usize) -> usize {
-  let mut cups = parse(i);
+pub fn part2(cups: &[usize], num_moves: usize) -> usize {
+  let mut cups = cups.to_vec();
   cups.extend(cups.iter().copied().max().unwrap() + 1..=1_000_000);
   let mut current_cup = cups[0];
   let mut cups = to_next_prev_pairs(&cups);
@@ -95,14 +142,14 @@ pub fn part2(i: &String, num_moves: usize) -> usize {
 fn to_next_prev_pairs(cups: &[usize]) -> Vec<(usize, usize)> {
   let mut cups_next_prev_pairs = vec![(0, 0); cups.len() + 1];
   for window in cups.windows(3) {
-    cups_next_prev_pairs[window[1] as usize] = (window[0], window[2]);
+    cups_next_prev_pairs[window[1]] = (window[0], window[2]);
   }
-  cups_next_prev_pairs[cups[0] as usize] = (cups[cups.len() - 1], cups[1]);
-  cups_next_prev_pairs[cups[cups.len() - 1] as usize] = (cups[cups.len() - 2], cups[0]);
+  cups_next_prev_pairs[cups[0]] = (cups[cups.len() - 1], cups[1]);
+  cups_next_prev_pairs[cups[cups.len() - 1]] = (cups[cups.len() - 2], cups[0]);
   cups_next_prev_pairs
 }
 
-fn play_round_linked(cups: &mut Vec<(usize, usize)>, current_cup: &mut usize) {
+fn play_round_linked(cups: &mut [(usize, usize)], current_cup: &mut usize) {
   let mut destination = if *current_cup == 1 {
     cups.len() - 1
   } else {
@@ -111,7 +158,7 @@ fn play_round_linked(cups: &mut Vec<(usize, usize)>, current_cup: &mut usize) {
   let removed1 = cups[*current_cup].1;
   let removed2 = cups[removed1].1;
   let removed3 = cups[removed2].1;
-  while vec![removed1, removed2, removed3].contains(&destination) {
+  while [removed1, removed2, removed3].contains(&destination) {
     destination = if destination == 1 {
       cups.len() - 1
     } else {
@@ -0,0 +1,99 @@
+use std::fmt;
+use std::path::PathBuf;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Errors produced while finding, reading or parsing puzzle input.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+  /// Part of the input didn't match the expected format. `line` and `column`
+  /// are 1-based and `text` is the offending fragment of the input.
+  Parse {
+    line: usize,
+    column: usize,
+    text: String,
+    message: String,
+  },
+  /// There is no input file for `day` at `path`.
+  MissingInput { day: u32, path: PathBuf },
+  /// Reading the input at `path` failed.
+  Io { path: PathBuf, message: String },
+}
+
+impl Error {
+  pub fn parse(line: usize, column: usize, text: &str, message: impl Into<String>) -> Self {
+    Error::Parse {
+      line,
+      column,
+      text: text.into(),
+      message: message.into(),
+    }
+  }
+
+  /// Build a parse error for `fragment`, which must be a slice of `input`,
+  /// finding its line and column from where it sits in `input`.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use advent_of_code_2020::Error;
+  ///
+  /// let input = "nop +0\nfoo +1";
+  /// let err = Error::at(input, &input[7..10], "expected an operation");
+  /// assert_eq!(err, Error::parse(2, 1, "foo", "expected an operation"));
+  /// assert_eq!(
+  ///   err.to_string(),
+  ///   "expected an operation at line 2, column 1: \"foo\""
+  /// );
+  /// ```
+  pub fn at(input: &str, fragment: &str, message: impl Into<String>) -> Self {
+    let offset = (fragment.as_ptr() as usize)
+      .saturating_sub(input.as_ptr() as usize)
+      .min(input.len());
+    let before = &input[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+    let column = before[line_start..].chars().count() + 1;
+    Error::parse(line, column, fragment, message)
+  }
+
+  /// Convert a `peg` error from parsing `parsed`, a slice of `input`, into a
+  /// parse error pointing at the rest of the line where parsing stopped.
+  pub fn from_peg(
+    input: &str,
+    parsed: &str,
+    err: peg::error::ParseError<peg::str::LineCol>,
+  ) -> Self {
+    let rest = &parsed[err.location.offset.min(parsed.len())..];
+    let text = rest.lines().next().unwrap_or(rest);
+    Error::at(input, text, format!("expected {}", err.expected))
+  }
+}
+
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Error::Parse {
+        line,
+        column,
+        text,
+        message,
+      } => write!(
+        f,
+        "{} at line {}, column {}: {:?}",
+        message, line, column, text
+      ),
+      Error::MissingInput { day, path } => write!(
+        f,
+        "no input for day {}, expected a file at {}",
+        day,
+        path.display()
+      ),
+      Error::Io { path, message } => {
+        write!(f, "unable to read {}: {}", path.display(), message)
+      }
+    }
+  }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,274 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::{Error, Result};
+
+/// A binary operator
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Op {
+  Add,
+  Sub,
+  Mul,
+  Div,
+}
+
+impl Op {
+  pub const ALL: [Op; 4] = [Op::Add, Op::Sub, Op::Mul, Op::Div];
+
+  pub fn symbol(self) -> char {
+    match self {
+      Op::Add => '+',
+      Op::Sub => '-',
+      Op::Mul => '*',
+      Op::Div => '/',
+    }
+  }
+
+  fn from_symbol(c: char) -> Option<Self> {
+    Self::ALL.iter().copied().find(|op| op.symbol() == c)
+  }
+
+  /// Apply the operator, or `None` on overflow or division by zero.
+  /// Division rounds towards zero.
+  pub fn apply(self, a: i64, b: i64) -> Option<i64> {
+    match self {
+      Op::Add => a.checked_add(b),
+      Op::Sub => a.checked_sub(b),
+      Op::Mul => a.checked_mul(b),
+      Op::Div => a.checked_div(b),
+    }
+  }
+}
+
+/// Which side a chain of operators at the same level groups from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Assoc {
+  /// `a - b - c` is `(a - b) - c`
+  Left,
+  /// `a - b - c` is `a - (b - c)`
+  Right,
+}
+
+/// The level and associativity of each operator an expression may use.
+/// Higher levels bind tighter, and operators missing from the table are
+/// parse errors.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Precedence {
+  operators: HashMap<Op, (u32, Assoc)>,
+}
+
+impl Precedence {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Add `op` at `level`, replacing any earlier entry for it
+  pub fn with(mut self, op: Op, level: u32, assoc: Assoc) -> Self {
+    self.operators.insert(op, (level, assoc));
+    self
+  }
+
+  /// Every operator at the same level, so they apply left to right
+  pub fn left_to_right() -> Self {
+    Op::ALL
+      .iter()
+      .fold(Self::new(), |table, &op| table.with(op, 1, Assoc::Left))
+  }
+
+  /// The usual rules, with `*` and `/` before `+` and `-`
+  pub fn standard() -> Self {
+    Self::new()
+      .with(Op::Add, 1, Assoc::Left)
+      .with(Op::Sub, 1, Assoc::Left)
+      .with(Op::Mul, 2, Assoc::Left)
+      .with(Op::Div, 2, Assoc::Left)
+  }
+
+  pub fn get(&self, op: Op) -> Option<(u32, Assoc)> {
+    self.operators.get(&op).copied()
+  }
+}
+
+/// A parsed expression. `Display` prints it with every binary operation in
+/// parentheses, so the grouping the precedence table chose is visible.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expr {
+  Num(i64),
+  Neg(Box<Expr>),
+  Binary(Op, Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+  /// The value of the expression, or `None` on overflow or division by zero
+  pub fn eval(&self) -> Option<i64> {
+    match self {
+      Expr::Num(n) => Some(*n),
+      Expr::Neg(e) => e.eval()?.checked_neg(),
+      Expr::Binary(op, a, b) => op.apply(a.eval()?, b.eval()?),
+    }
+  }
+}
+
+impl fmt::Display for Expr {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Expr::Num(n) => write!(f, "{}", n),
+      Expr::Neg(e) => write!(f, "-{}", e),
+      Expr::Binary(op, a, b) => write!(f, "({} {} {})", a, op.symbol(), b),
+    }
+  }
+}
+
+/// Parse an expression of whole numbers, the operators in `table`, unary
+/// minus and parentheses, with any whitespace between them. Nesting deeper
+/// than `MAX_NESTING` is an error.
+///
+/// # Example
+///
+/// ```
+/// use advent_of_code_2020::expression::{parse, Assoc, Op, Precedence};
+///
+/// let expr = parse("12 * 3 + -4 * 5", &Precedence::standard()).unwrap();
+/// assert_eq!(expr.to_string(), "((12 * 3) + (-4 * 5))");
+/// assert_eq!(expr.eval(), Some(16));
+///
+/// let backwards = Precedence::new()
+///   .with(Op::Sub, 1, Assoc::Right)
+///   .with(Op::Div, 1, Assoc::Right);
+/// assert_eq!(parse("100 / 10 - 8", &backwards).unwrap().eval(), Some(50));
+///
+/// let err = parse("(1 + 2", &Precedence::standard()).unwrap_err();
+/// assert_eq!(err.to_string(), "unclosed `(` at line 1, column 1: \"(\"");
+/// let err = parse("1 + 2 * 3", &Precedence::new().with(Op::Add, 1, Assoc::Left)).unwrap_err();
+/// assert_eq!(
+///   err.to_string(),
+///   "`*` isn't in the precedence table at line 1, column 7: \"*\""
+/// );
+/// ```
+pub fn parse(i: &str, table: &Precedence) -> Result<Expr> {
+  parse_within(i, i, table)
+}
+
+/// Like `parse`, for an expression that is a slice of a larger `input` so
+/// errors point at the right place in it.
+pub fn parse_within(input: &str, i: &str, table: &Precedence) -> Result<Expr> {
+  let mut parser = Parser {
+    input,
+    rest: i,
+    table,
+    depth: 0,
+  };
+  let expr = parser.expr(0)?;
+  match parser.peek() {
+    None => Ok(expr),
+    Some(')') => Err(parser.error_at_next("unmatched `)`")),
+    Some(_) => Err(parser.error_at_next("expected an operator")),
+  }
+}
+
+/// How deep parentheses, unary minus and right hand operands may nest, so
+/// the recursive parser can't overflow the stack
+pub const MAX_NESTING: usize = 256;
+
+struct Parser<'a, 't> {
+  input: &'a str,
+  rest: &'a str,
+  table: &'t Precedence,
+  /// How many of `MAX_NESTING` levels are in use
+  depth: usize,
+}
+
+impl<'a> Parser<'a, '_> {
+  /// The next character that isn't whitespace
+  fn peek(&mut self) -> Option<char> {
+    self.rest = self.rest.trim_start();
+    self.rest.chars().next()
+  }
+
+  /// Consume the next character, returning it as a slice of the input
+  fn advance(&mut self) -> &'a str {
+    let len = self.rest.chars().next().map_or(0, char::len_utf8);
+    let (taken, rest) = self.rest.split_at(len);
+    self.rest = rest;
+    taken
+  }
+
+  fn error_at_next(&mut self, message: impl Into<String>) -> Error {
+    self.peek();
+    let len = self.rest.chars().next().map_or(0, char::len_utf8);
+    Error::at(self.input, &self.rest[..len], message)
+  }
+
+  /// Parse with `parse` one level deeper, failing at `at` past `MAX_NESTING`
+  fn nested(&mut self, at: &'a str, parse: impl FnOnce(&mut Self) -> Result<Expr>) -> Result<Expr> {
+    if self.depth == MAX_NESTING {
+      return Err(Error::at(self.input, at, "expression is nested too deeply"));
+    }
+    self.depth += 1;
+    let expr = parse(self);
+    self.depth -= 1;
+    expr
+  }
+
+  /// Binary operations whose operators are at least at level `min`
+  fn expr(&mut self, min: u32) -> Result<Expr> {
+    let mut lhs = self.operand()?;
+    while let Some(op) = self.peek().and_then(Op::from_symbol) {
+      let (level, assoc) = self.table.get(op).ok_or_else(|| {
+        self.error_at_next(format!("`{}` isn't in the precedence table", op.symbol()))
+      })?;
+      if level < min {
+        break;
+      }
+      let symbol = self.advance();
+      // Nothing binds tighter than the highest level, so only an operand
+      // can follow a left associative operator there
+      let next = match assoc {
+        Assoc::Left => level.checked_add(1),
+        Assoc::Right => Some(level),
+      };
+      let rhs = self.nested(symbol, |parser| match next {
+        Some(next) => parser.expr(next),
+        None => parser.operand(),
+      })?;
+      lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+    }
+    Ok(lhs)
+  }
+
+  /// A number, a negated operand or an expression in parentheses
+  fn operand(&mut self) -> Result<Expr> {
+    match self.peek() {
+      Some('(') => {
+        let open = self.advance();
+        let expr = self.nested(open, |parser| parser.expr(0))?;
+        match self.peek() {
+          Some(')') => {
+            self.advance();
+            Ok(expr)
+          }
+          None => Err(Error::at(self.input, open, "unclosed `(`")),
+          Some(_) => Err(self.error_at_next("expected an operator or `)`")),
+        }
+      }
+      Some('-') => {
+        let minus = self.advance();
+        let operand = self.nested(minus, Self::operand)?;
+        Ok(Expr::Neg(Box::new(operand)))
+      }
+      Some(c) if c.is_ascii_digit() => {
+        let len = self
+          .rest
+          .find(|c: char| !c.is_ascii_digit())
+          .unwrap_or(self.rest.len());
+        let (digits, rest) = self.rest.split_at(len);
+        self.rest = rest;
+        digits
+          .parse()
+          .map(Expr::Num)
+          .map_err(|_| Error::at(self.input, digits, "number is too large"))
+      }
+      _ => Err(self.error_at_next("expected a number, `-` or `(`")),
+    }
+  }
+}
@@ -0,0 +1,356 @@
+use std::collections::{HashMap, HashSet};
+
+use regex::{Regex, RegexBuilder};
+
+use crate::{Error, Result};
+
+/// One thing a sequence expects next: a literal character or another rule
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Symbol {
+  Char(char),
+  Rule(u64),
+}
+
+/// Symbols that must match one after the other
+pub type Sequence = Vec<Symbol>;
+
+/// Numbered rules, each a list of alternative sequences. Rules can refer to
+/// each other and to themselves in any position, so both left and right
+/// recursion are fine.
+///
+/// # Example
+///
+/// ```
+/// use advent_of_code_2020::grammar::{parse_rule, Grammar};
+///
+/// let mut grammar = Grammar::parse("0: 1 2\n1: \"a\" | 1 \"a\"\n2: \"b\" | \"b\" 2").unwrap();
+/// assert!(grammar.matches(0, "aaabb"));
+/// assert!(!grammar.matches(0, "aaa"));
+/// // Balanced `a`s and `b`s, which no regular expression can match
+/// grammar.insert(0, parse_rule("\"a\" \"b\" | \"a\" 0 \"b\"").unwrap());
+/// assert!(grammar.matches(0, "aaabbb"));
+/// assert!(!grammar.matches(0, "aaabb"));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Grammar {
+  rules: HashMap<u64, Vec<Sequence>>,
+  /// Rules that can match without consuming anything, kept up to date as
+  /// rules change
+  nullable: HashSet<u64>,
+}
+
+/// A partly matched alternative: `dot` symbols of it have matched so far,
+/// starting at position `origin` of the message
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct Item {
+  rule: u64,
+  alternative: usize,
+  dot: usize,
+  origin: usize,
+}
+
+impl Grammar {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Parse one `<id>: <rule>` per line, in the form `parse_rule` reads
+  pub fn parse(i: &str) -> Result<Self> {
+    Self::parse_within(i, i)
+  }
+
+  /// Like `parse`, for rules that are a slice of a larger `input` so errors
+  /// point at the right place in it. Every rule that's referred to must be
+  /// defined.
+  pub fn parse_within(input: &str, i: &str) -> Result<Self> {
+    let mut grammar = Self::new();
+    let mut references = Vec::new();
+    for line in i.lines().filter(|line| !line.trim().is_empty()) {
+      let (id, rule) = line
+        .split_once(':')
+        .ok_or_else(|| Error::at(input, line, "expected `<id>: <rule>`"))?;
+      let id = id
+        .trim()
+        .parse::<u64>()
+        .map_err(|_| Error::at(input, id, "expected a rule number"))?;
+      if grammar.rules.contains_key(&id) {
+        return Err(Error::at(input, line, "rule is already defined"));
+      }
+      grammar
+        .rules
+        .insert(id, parse_rule_within(input, rule, &mut references)?);
+    }
+    if let Some((text, _)) = references
+      .into_iter()
+      .find(|(_, id)| !grammar.rules.contains_key(id))
+    {
+      return Err(Error::at(input, text, "rule is never defined"));
+    }
+    grammar.nullable = grammar.find_nullable();
+    Ok(grammar)
+  }
+
+  /// Add or replace a rule, returning the one it replaced
+  pub fn insert(&mut self, id: u64, alternatives: Vec<Sequence>) -> Option<Vec<Sequence>> {
+    let replaced = self.rules.insert(id, alternatives);
+    self.nullable = self.find_nullable();
+    replaced
+  }
+
+  pub fn get(&self, id: u64) -> Option<&[Sequence]> {
+    self.rules.get(&id).map(Vec::as_slice)
+  }
+
+  fn alternatives(&self, id: u64) -> &[Sequence] {
+    // A rule that isn't defined has no way to match
+    self.get(id).unwrap_or(&[])
+  }
+
+  /// Rules that can match without consuming anything
+  fn find_nullable(&self) -> HashSet<u64> {
+    let mut nullable = HashSet::new();
+    loop {
+      let before = nullable.len();
+      for (&id, alternatives) in &self.rules {
+        let empty = alternatives.iter().any(|sequence| {
+          sequence
+            .iter()
+            .all(|symbol| matches!(symbol, Symbol::Rule(r) if nullable.contains(r)))
+        });
+        if empty {
+          nullable.insert(id);
+        }
+      }
+      if nullable.len() == before {
+        return nullable;
+      }
+    }
+  }
+
+  /// Whether rule `start` can reach itself again through the rules it
+  /// refers to, or reaches another rule that does
+  pub fn is_recursive(&self, start: u64) -> bool {
+    fn visit(grammar: &Grammar, id: u64, path: &mut Vec<u64>, done: &mut HashSet<u64>) -> bool {
+      if path.contains(&id) {
+        return true;
+      }
+      if !done.insert(id) {
+        return false;
+      }
+      path.push(id);
+      let recursive = grammar
+        .alternatives(id)
+        .iter()
+        .flatten()
+        .any(|symbol| match *symbol {
+          Symbol::Rule(rule) => visit(grammar, rule, path, done),
+          Symbol::Char(_) => false,
+        });
+      path.pop();
+      recursive
+    }
+    visit(self, start, &mut Vec::new(), &mut HashSet::new())
+  }
+
+  /// Compile rule `start` to an anchored regular expression. Returns `None`
+  /// if the rule is recursive, refers to a rule that isn't defined or makes
+  /// too big a regex.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use advent_of_code_2020::grammar::Grammar;
+  ///
+  /// let grammar = Grammar::parse("0: 1 2 | 2 1\n1: \"a\"\n2: \"b\" | \"c\" 1").unwrap();
+  /// let regex = grammar.to_regex(0).unwrap();
+  /// assert_eq!(regex.as_str(), "^(?:a(?:b|ca)|(?:b|ca)a)$");
+  /// assert!(regex.is_match("caa"));
+  ///
+  /// let recursive = Grammar::parse("0: \"a\" | \"a\" 0").unwrap();
+  /// assert!(recursive.to_regex(0).is_none());
+  /// ```
+  pub fn to_regex(&self, start: u64) -> Option<Regex> {
+    if self.is_recursive(start) {
+      return None;
+    }
+    let mut patterns = HashMap::new();
+    let pattern = self.pattern(start, &mut patterns)?;
+    RegexBuilder::new(&format!("^{}$", pattern))
+      .size_limit(1 << 26)
+      .build()
+      .ok()
+  }
+
+  /// The regex for a rule that isn't recursive, built once per rule
+  fn pattern(&self, id: u64, patterns: &mut HashMap<u64, String>) -> Option<String> {
+    if let Some(pattern) = patterns.get(&id) {
+      return Some(pattern.clone());
+    }
+    let mut alternatives = Vec::new();
+    for sequence in self.get(id)? {
+      let mut pattern = String::new();
+      for symbol in sequence {
+        match *symbol {
+          Symbol::Char(c) => pattern.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+          Symbol::Rule(rule) => pattern.push_str(&self.pattern(rule, patterns)?),
+        }
+      }
+      alternatives.push(pattern);
+    }
+    let pattern = match &alternatives[..] {
+      // A rule with no alternatives can't match, and `(?:)` would match anything
+      [] => return None,
+      [single] => single.clone(),
+      _ => format!("(?:{})", alternatives.join("|")),
+    };
+    patterns.insert(id, pattern.clone());
+    Some(pattern)
+  }
+
+  /// A matcher for rule `start`, using a regex when the rule compiles to
+  /// one and the Earley parser when it doesn't.
+  pub fn matcher(&self, start: u64) -> Matcher<'_> {
+    match self.to_regex(start) {
+      Some(regex) => Matcher::Regex(regex),
+      None => Matcher::Earley {
+        grammar: self,
+        start,
+      },
+    }
+  }
+
+  /// Whether all of `message` matches rule `start`, using an Earley parser.
+  pub fn matches(&self, start: u64, message: &str) -> bool {
+    let chars: Vec<char> = message.chars().collect();
+    let nullable = &self.nullable;
+    let mut sets: Vec<Vec<Item>> = vec![Vec::new(); chars.len() + 1];
+    let mut seen: Vec<HashSet<Item>> = vec![HashSet::new(); chars.len() + 1];
+    let mut add = |sets: &mut Vec<Vec<Item>>, position: usize, item: Item| {
+      if seen[position].insert(item) {
+        sets[position].push(item);
+      }
+    };
+
+    for alternative in 0..self.alternatives(start).len() {
+      let item = Item {
+        rule: start,
+        alternative,
+        dot: 0,
+        origin: 0,
+      };
+      add(&mut sets, 0, item);
+    }
+
+    for position in 0..=chars.len() {
+      let mut next = 0;
+      while next < sets[position].len() {
+        let item = sets[position][next];
+        next += 1;
+        let advanced = Item {
+          dot: item.dot + 1,
+          ..item
+        };
+        match self.alternatives(item.rule)[item.alternative].get(item.dot) {
+          Some(&Symbol::Char(c)) => {
+            if chars.get(position) == Some(&c) {
+              add(&mut sets, position + 1, advanced);
+            }
+          }
+          Some(&Symbol::Rule(rule)) => {
+            for alternative in 0..self.alternatives(rule).len() {
+              let predicted = Item {
+                rule,
+                alternative,
+                dot: 0,
+                origin: position,
+              };
+              add(&mut sets, position, predicted);
+            }
+            // A rule matching nothing completes straight away, but it may
+            // have done so before this item was here to be advanced
+            if nullable.contains(&rule) {
+              add(&mut sets, position, advanced);
+            }
+          }
+          None => {
+            let mut waiting = 0;
+            while waiting < sets[item.origin].len() {
+              let parent = sets[item.origin][waiting];
+              waiting += 1;
+              let expected = self.alternatives(parent.rule)[parent.alternative].get(parent.dot);
+              if expected == Some(&Symbol::Rule(item.rule)) {
+                let completed = Item {
+                  dot: parent.dot + 1,
+                  ..parent
+                };
+                add(&mut sets, position, completed);
+              }
+            }
+          }
+        }
+      }
+    }
+
+    sets[chars.len()].iter().any(|item| {
+      item.rule == start
+        && item.origin == 0
+        && item.dot == self.alternatives(start)[item.alternative].len()
+    })
+  }
+}
+
+/// Matches messages against one rule of a grammar
+#[derive(Clone, Debug)]
+pub enum Matcher<'a> {
+  /// The rule compiled to a regular expression
+  Regex(Regex),
+  /// The rule is recursive, so messages go through the Earley parser
+  Earley { grammar: &'a Grammar, start: u64 },
+}
+
+impl Matcher<'_> {
+  pub fn matches(&self, message: &str) -> bool {
+    match self {
+      Matcher::Regex(regex) => regex.is_match(message),
+      Matcher::Earley { grammar, start } => grammar.matches(*start, message),
+    }
+  }
+}
+
+/// Parse a rule as alternatives separated by `|`, each a sequence of rule
+/// numbers and quoted literals like `"a"`, separated by spaces.
+pub fn parse_rule(rule: &str) -> Result<Vec<Sequence>> {
+  parse_rule_within(rule, rule, &mut Vec::new())
+}
+
+/// Parse `rule`, a slice of `input`, noting each rule number it refers to
+fn parse_rule_within<'a>(
+  input: &str,
+  rule: &'a str,
+  references: &mut Vec<(&'a str, u64)>,
+) -> Result<Vec<Sequence>> {
+  let mut alternatives = Vec::new();
+  for alternative in rule.split('|') {
+    let mut sequence = Vec::new();
+    for word in alternative.split_whitespace() {
+      if let Some(literal) = word.strip_prefix('"') {
+        let literal = literal
+          .strip_suffix('"')
+          .filter(|literal| !literal.is_empty())
+          .ok_or_else(|| Error::at(input, word, "expected a quoted literal"))?;
+        sequence.extend(literal.chars().map(Symbol::Char));
+      } else {
+        let id = word
+          .parse()
+          .map_err(|_| Error::at(input, word, "expected a rule number or a quoted literal"))?;
+        references.push((word, id));
+        sequence.push(Symbol::Rule(id));
+      }
+    }
+    if sequence.is_empty() {
+      return Err(Error::at(input, alternative, "expected a rule"));
+    }
+    alternatives.push(sequence);
+  }
+  Ok(alternatives)
+}
@@ -0,0 +1,336 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use crate::{Error, Result};
+
+/// A position in a grid as `(x, y)`, with `(0, 0)` the top left cell
+pub type Position = (usize, usize);
+
+/// A step between positions as `(dx, dy)`, with `y` growing downwards
+pub type Direction = (isize, isize);
+
+/// Up, right, down and left
+pub const ORTHOGONAL: [Direction; 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// Every direction to a touching cell, including diagonals, clockwise from up
+pub const ALL_DIRECTIONS: [Direction; 8] = [
+  (0, -1),
+  (1, -1),
+  (1, 0),
+  (1, 1),
+  (0, 1),
+  (-1, 1),
+  (-1, 0),
+  (-1, -1),
+];
+
+/// A rectangular grid of cells stored row by row.
+///
+/// # Example
+///
+/// ```
+/// use advent_of_code_2020::grid::Grid;
+///
+/// let grid = Grid::parse(".#.\n..#\n###", |c| match c {
+///   '.' => Some(false),
+///   '#' => Some(true),
+///   _ => None,
+/// })
+/// .unwrap();
+/// assert_eq!((grid.width(), grid.height()), (3, 3));
+/// assert_eq!(grid.get(1, 0), Some(&true));
+/// assert_eq!(grid.get(3, 0), None);
+/// assert_eq!(grid.get_wrapping(4, 3), &true);
+/// assert_eq!(grid.neighbours8((0, 0)).filter(|&p| grid[p]).count(), 1);
+/// assert_eq!(grid.ray((0, 2), (1, -1)).collect::<Vec<_>>(), vec![(1, 1), (2, 0)]);
+/// assert!((0..8).all(|n| grid.orientation(n) == grid.orientations()[n]));
+/// assert_eq!(
+///   grid.render(|&b| if b { '#' } else { '.' }),
+///   ".#.\n..#\n###\n"
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+  width: usize,
+  height: usize,
+  cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+  /// A `width` by `height` grid with every cell set to `fill`
+  pub fn new(width: usize, height: usize, fill: T) -> Self
+  where
+    T: Clone,
+  {
+    Self {
+      width,
+      height,
+      cells: vec![fill; width * height],
+    }
+  }
+
+  /// Build a grid from equal length rows, or `None` if they differ in length
+  pub fn from_rows(rows: Vec<Vec<T>>) -> Option<Self> {
+    let height = rows.len();
+    let width = rows.first().map_or(0, |row| row.len());
+    if rows.iter().any(|row| row.len() != width) {
+      return None;
+    }
+    Some(Self {
+      width,
+      height,
+      cells: rows.into_iter().flatten().collect(),
+    })
+  }
+
+  /// Build a grid by calling `f` for every position, row by row
+  pub fn from_fn(width: usize, height: usize, f: impl FnMut(Position) -> T) -> Self {
+    let cells = (0..height)
+      .flat_map(|y| (0..width).map(move |x| (x, y)))
+      .map(f)
+      .collect();
+    Self {
+      width,
+      height,
+      cells,
+    }
+  }
+
+  /// Parse a character map with one row per line, skipping blank lines.
+  /// `cell` returns `None` for characters that aren't allowed in the map.
+  pub fn parse(i: &str, cell: impl FnMut(char) -> Option<T>) -> Result<Self> {
+    Self::parse_within(i, i, cell)
+  }
+
+  /// Like `parse`, for a map that is a slice of a larger `input` so errors
+  /// point at the right place in it.
+  pub fn parse_within(
+    input: &str,
+    i: &str,
+    mut cell: impl FnMut(char) -> Option<T>,
+  ) -> Result<Self> {
+    let mut rows: Vec<Vec<T>> = Vec::new();
+    for line in i.lines().filter(|line| !line.is_empty()) {
+      let row = line
+        .char_indices()
+        .map(|(idx, c)| {
+          cell(c).ok_or_else(|| {
+            Error::at(
+              input,
+              &line[idx..idx + c.len_utf8()],
+              format!("unexpected `{}` in map", c),
+            )
+          })
+        })
+        .collect::<Result<Vec<T>>>()?;
+      if let Some(first) = rows.first() {
+        if row.len() != first.len() {
+          return Err(Error::at(
+            input,
+            line,
+            format!("expected a row of {} cells", first.len()),
+          ));
+        }
+      }
+      rows.push(row);
+    }
+    Ok(Self::from_rows(rows).expect("rows were checked to be the same length"))
+  }
+
+  pub fn width(&self) -> usize {
+    self.width
+  }
+
+  pub fn height(&self) -> usize {
+    self.height
+  }
+
+  pub fn contains(&self, (x, y): Position) -> bool {
+    x < self.width && y < self.height
+  }
+
+  pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+    if self.contains((x, y)) {
+      self.cells.get(y * self.width + x)
+    } else {
+      None
+    }
+  }
+
+  pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+    if self.contains((x, y)) {
+      self.cells.get_mut(y * self.width + x)
+    } else {
+      None
+    }
+  }
+
+  /// The cell at `(x, y)` as if the grid repeated forever in every direction
+  pub fn get_wrapping(&self, x: usize, y: usize) -> &T {
+    &self[(x % self.width, y % self.height)]
+  }
+
+  /// The position one `direction` step from `position`, if it's in the grid
+  pub fn step(&self, (x, y): Position, (dx, dy): Direction) -> Option<Position> {
+    let x = x.checked_add_signed(dx)?;
+    let y = y.checked_add_signed(dy)?;
+    Some((x, y)).filter(|&p| self.contains(p))
+  }
+
+  /// Positions above, right of, below and left of `position` that are in the grid
+  pub fn neighbours4(&self, position: Position) -> impl Iterator<Item = Position> + '_ {
+    ORTHOGONAL
+      .iter()
+      .filter_map(move |&direction| self.step(position, direction))
+  }
+
+  /// Positions touching `position`, including diagonally, that are in the grid
+  pub fn neighbours8(&self, position: Position) -> impl Iterator<Item = Position> + '_ {
+    ALL_DIRECTIONS
+      .iter()
+      .filter_map(move |&direction| self.step(position, direction))
+  }
+
+  /// Positions visited stepping in `direction` from `position`, not including
+  /// `position` itself, until the edge of the grid
+  pub fn ray(
+    &self,
+    position: Position,
+    direction: Direction,
+  ) -> impl Iterator<Item = Position> + '_ {
+    let mut current = position;
+    std::iter::from_fn(move || {
+      current = self.step(current, direction)?;
+      Some(current)
+    })
+  }
+
+  /// Every position, row by row
+  pub fn positions(&self) -> impl Iterator<Item = Position> {
+    let width = self.width;
+    (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+  }
+
+  /// Every cell, row by row
+  pub fn iter(&self) -> impl Iterator<Item = &T> {
+    self.cells.iter()
+  }
+
+  pub fn row(&self, y: usize) -> &[T] {
+    &self.cells[y * self.width..(y + 1) * self.width]
+  }
+
+  pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+    // `chunks` panics on 0, and an empty grid has no rows anyway
+    self.cells.chunks(self.width.max(1))
+  }
+
+  pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+    self.cells.iter().skip(x).step_by(self.width.max(1))
+  }
+
+  pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+    Grid {
+      width: self.width,
+      height: self.height,
+      cells: self.cells.iter().map(f).collect(),
+    }
+  }
+
+  /// Render the grid one row per line, with a character per cell
+  pub fn render(&self, mut f: impl FnMut(&T) -> char) -> String {
+    let mut out = String::with_capacity((self.width + 1) * self.height);
+    for row in self.rows() {
+      out.extend(row.iter().map(&mut f));
+      out.push('\n');
+    }
+    out
+  }
+}
+
+impl<T: Clone> Grid<T> {
+  /// The `width` by `height` part of the grid with its top left at `(x, y)`
+  pub fn sub_grid(&self, (x, y): Position, width: usize, height: usize) -> Self {
+    Self::from_fn(width, height, |(dx, dy)| self[(x + dx, y + dy)].clone())
+  }
+
+  /// Rotate a quarter turn clockwise
+  pub fn rotate(&self) -> Self {
+    Self::from_fn(self.height, self.width, |(x, y)| {
+      self[(y, self.height - 1 - x)].clone()
+    })
+  }
+
+  /// Flip top to bottom
+  pub fn flip_vertical(&self) -> Self {
+    Self::from_fn(self.width, self.height, |(x, y)| {
+      self[(x, self.height - 1 - y)].clone()
+    })
+  }
+
+  /// Orientation `n` of `orientations`, without building the others
+  pub fn orientation(&self, n: usize) -> Self {
+    let mut grid = if n % 8 >= 4 {
+      self.flip_vertical()
+    } else {
+      self.clone()
+    };
+    for _ in 0..n % 4 {
+      grid = grid.rotate();
+    }
+    grid
+  }
+
+  /// The 4 rotations of the grid followed by the 4 rotations of it flipped
+  pub fn orientations(&self) -> Vec<Self> {
+    let mut current = self.clone();
+    let mut all = Vec::with_capacity(8);
+    for _ in 0..4 {
+      let next = current.rotate();
+      all.push(current);
+      current = next;
+    }
+    current = current.flip_vertical();
+    for _ in 0..4 {
+      let next = current.rotate();
+      all.push(current);
+      current = next;
+    }
+    all
+  }
+}
+
+impl<T> Index<Position> for Grid<T> {
+  type Output = T;
+
+  fn index(&self, (x, y): Position) -> &T {
+    self.get(x, y).unwrap_or_else(|| {
+      panic!(
+        "({}, {}) is outside a {}x{} grid",
+        x, y, self.width, self.height
+      )
+    })
+  }
+}
+
+impl<T> IndexMut<Position> for Grid<T> {
+  fn index_mut(&mut self, (x, y): Position) -> &mut T {
+    let (width, height) = (self.width, self.height);
+    self
+      .get_mut(x, y)
+      .unwrap_or_else(|| panic!("({}, {}) is outside a {}x{} grid", x, y, width, height))
+  }
+}
+
+/// Prints each cell's `Display` one row per line
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    for row in self.rows() {
+      for cell in row {
+        write!(f, "{}", cell)?;
+      }
+      writeln!(f)?;
+    }
+    Ok(())
+  }
+}
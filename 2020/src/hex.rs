@@ -0,0 +1,239 @@
+use std::ops::{Add, Mul, Sub};
+
+use crate::{Error, Result};
+
+/// The six directions from a hexagon in a grid with flat sides to the east
+/// and west, listed clockwise from east.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HexDirection {
+  East,
+  Southeast,
+  Southwest,
+  West,
+  Northwest,
+  Northeast,
+}
+
+impl HexDirection {
+  pub const ALL: [HexDirection; 6] = [
+    HexDirection::East,
+    HexDirection::Southeast,
+    HexDirection::Southwest,
+    HexDirection::West,
+    HexDirection::Northwest,
+    HexDirection::Northeast,
+  ];
+
+  /// One step in this direction from the origin
+  pub fn offset(self) -> Hex {
+    match self {
+      HexDirection::East => Hex::new(1, 0),
+      HexDirection::Southeast => Hex::new(0, 1),
+      HexDirection::Southwest => Hex::new(-1, 1),
+      HexDirection::West => Hex::new(-1, 0),
+      HexDirection::Northwest => Hex::new(0, -1),
+      HexDirection::Northeast => Hex::new(1, -1),
+    }
+  }
+
+  /// Turn clockwise by `steps` sixths of a full turn
+  pub fn rotate(self, steps: i64) -> Self {
+    let index = Self::ALL.iter().position(|&d| d == self).unwrap_or(0) as i64;
+    Self::ALL[(index + steps).rem_euclid(6) as usize]
+  }
+
+  pub fn as_str(self) -> &'static str {
+    match self {
+      HexDirection::East => "e",
+      HexDirection::Southeast => "se",
+      HexDirection::Southwest => "sw",
+      HexDirection::West => "w",
+      HexDirection::Northwest => "nw",
+      HexDirection::Northeast => "ne",
+    }
+  }
+}
+
+/// A hexagon in axial coordinates. `q` grows to the east and `r` to the
+/// southeast, so the hexagon's cube coordinates are `(q, -q - r, r)`.
+///
+/// # Example
+///
+/// ```
+/// use advent_of_code_2020::hex::{parse_path, Hex, HexDirection};
+///
+/// let path = parse_path("nwwswee").unwrap();
+/// assert_eq!(Hex::from_path(&path), Hex::new(0, 0));
+///
+/// let tile = Hex::from_path(&parse_path("esew").unwrap());
+/// assert_eq!(tile, HexDirection::Southeast.offset());
+/// assert_eq!(tile.distance(Hex::new(-2, 0)), 3);
+/// assert_eq!(tile.rotate(1), HexDirection::Southwest.offset());
+/// assert_eq!(Hex::new(0, 0).ring(2).len(), 12);
+/// assert!(Hex::ORIGIN.ring(2).iter().all(|hex| hex.length() == 2));
+/// assert_eq!(Hex::new(0, 0).spiral(2).len(), 19);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Hex {
+  pub q: i64,
+  pub r: i64,
+}
+
+/// Cube coordinates of a hexagon, where `x + y + z == 0`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Cube {
+  pub x: i64,
+  pub y: i64,
+  pub z: i64,
+}
+
+impl Hex {
+  pub const ORIGIN: Hex = Hex { q: 0, r: 0 };
+
+  pub fn new(q: i64, r: i64) -> Self {
+    Self { q, r }
+  }
+
+  pub fn from_cube(cube: Cube) -> Self {
+    Self::new(cube.x, cube.z)
+  }
+
+  pub fn to_cube(self) -> Cube {
+    Cube {
+      x: self.q,
+      y: -self.q - self.r,
+      z: self.r,
+    }
+  }
+
+  /// Where following `path` from the origin ends up
+  pub fn from_path(path: &[HexDirection]) -> Self {
+    path
+      .iter()
+      .fold(Self::ORIGIN, |hex, &direction| hex.neighbour(direction))
+  }
+
+  pub fn neighbour(self, direction: HexDirection) -> Self {
+    self + direction.offset()
+  }
+
+  /// The six touching hexagons, clockwise from east
+  pub fn neighbours(self) -> [Hex; 6] {
+    HexDirection::ALL.map(|direction| self.neighbour(direction))
+  }
+
+  /// Steps from the origin
+  pub fn length(self) -> i64 {
+    let cube = self.to_cube();
+    (cube.x.abs() + cube.y.abs() + cube.z.abs()) / 2
+  }
+
+  /// Fewest steps between the two hexagons
+  pub fn distance(self, other: Self) -> i64 {
+    (self - other).length()
+  }
+
+  /// Turn around the origin clockwise by `steps` sixths of a full turn
+  pub fn rotate(self, steps: i64) -> Self {
+    (0..steps.rem_euclid(6)).fold(self, |hex, _| {
+      let Cube { x, y, z } = hex.to_cube();
+      Self::from_cube(Cube {
+        x: -z,
+        y: -x,
+        z: -y,
+      })
+    })
+  }
+
+  /// Turn around `center` clockwise by `steps` sixths of a full turn
+  pub fn rotate_around(self, center: Self, steps: i64) -> Self {
+    (self - center).rotate(steps) + center
+  }
+
+  /// Hexagons exactly `radius` steps away, clockwise from the northwest corner
+  pub fn ring(self, radius: u32) -> Vec<Hex> {
+    if radius == 0 {
+      return vec![self];
+    }
+    let radius = radius as i64;
+    let mut hex = self + HexDirection::Northwest.offset() * radius;
+    let mut ring = Vec::with_capacity(6 * radius as usize);
+    for &direction in HexDirection::ALL.iter() {
+      for _ in 0..radius {
+        ring.push(hex);
+        hex = hex.neighbour(direction);
+      }
+    }
+    ring
+  }
+
+  /// Hexagons up to `radius` steps away, ring by ring outwards from this one
+  pub fn spiral(self, radius: u32) -> Vec<Hex> {
+    (0..=radius).flat_map(|r| self.ring(r)).collect()
+  }
+}
+
+impl Add for Hex {
+  type Output = Hex;
+
+  fn add(self, other: Hex) -> Hex {
+    Hex::new(self.q + other.q, self.r + other.r)
+  }
+}
+
+impl Sub for Hex {
+  type Output = Hex;
+
+  fn sub(self, other: Hex) -> Hex {
+    Hex::new(self.q - other.q, self.r - other.r)
+  }
+}
+
+impl Mul<i64> for Hex {
+  type Output = Hex;
+
+  fn mul(self, n: i64) -> Hex {
+    Hex::new(self.q * n, self.r * n)
+  }
+}
+
+impl From<Hex> for [i64; 2] {
+  fn from(hex: Hex) -> Self {
+    [hex.q, hex.r]
+  }
+}
+
+impl From<[i64; 2]> for Hex {
+  fn from([q, r]: [i64; 2]) -> Self {
+    Hex::new(q, r)
+  }
+}
+
+/// Parse a path of `e`, `se`, `sw`, `w`, `nw` and `ne` steps with no separators
+pub fn parse_path(path: &str) -> Result<Vec<HexDirection>> {
+  parse_path_within(path, path)
+}
+
+/// Like `parse_path`, for a path that is a slice of a larger `input` so
+/// errors point at the right place in it.
+pub fn parse_path_within(input: &str, path: &str) -> Result<Vec<HexDirection>> {
+  let mut directions = Vec::new();
+  let mut rest = path;
+  while !rest.is_empty() {
+    let direction = HexDirection::ALL
+      .iter()
+      .copied()
+      .find(|d| rest.starts_with(d.as_str()))
+      .ok_or_else(|| {
+        let len = rest.chars().next().map_or(0, char::len_utf8);
+        Error::at(
+          input,
+          &rest[..len],
+          "expected one of `e`, `se`, `sw`, `w`, `nw` or `ne`",
+        )
+      })?;
+    directions.push(direction);
+    rest = &rest[direction.as_str().len()..];
+  }
+  Ok(directions)
+}
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use crate::{Error, Result};
+
+/// Environment variable naming the directory that holds `dayNN.txt` inputs.
+pub const INPUT_DIR_VAR: &str = "AOC_INPUT_DIR";
+
+/// Inputs checked in next to the solutions.
+pub const DEFAULT_INPUT_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/day");
+
+/// Finds puzzle inputs in a directory of `dayNN.txt` files and keeps each
+/// one in memory after the first read, so repeated runs don't touch the disk.
+///
+/// # Example
+///
+/// ```
+/// use advent_of_code_2020::input::Inputs;
+///
+/// let dir = std::env::temp_dir().join("aoc-2020-inputs-example");
+/// std::fs::create_dir_all(&dir).unwrap();
+/// std::fs::write(dir.join("day01.txt"), "1721\n979\n").unwrap();
+///
+/// let mut inputs = Inputs::new(&dir);
+/// assert_eq!(inputs.get(1).unwrap(), "1721\n979\n");
+/// assert!(inputs.get(2).is_err());
+///
+/// inputs.insert(2, "1-3 a: abcde".into());
+/// assert_eq!(inputs.get(2).unwrap(), "1-3 a: abcde");
+/// ```
+#[derive(Clone, Debug)]
+pub struct Inputs {
+  dir: PathBuf,
+  cache: HashMap<u32, String>,
+}
+
+impl Inputs {
+  pub fn new(dir: impl Into<PathBuf>) -> Self {
+    Self {
+      dir: dir.into(),
+      cache: HashMap::new(),
+    }
+  }
+
+  /// Use the directory in `AOC_INPUT_DIR` when set, otherwise the inputs
+  /// checked in to this crate.
+  pub fn from_env() -> Self {
+    match std::env::var_os(INPUT_DIR_VAR) {
+      Some(dir) => Self::new(dir),
+      None => Self::new(DEFAULT_INPUT_DIR),
+    }
+  }
+
+  pub fn dir(&self) -> &Path {
+    &self.dir
+  }
+
+  /// Where the input for `day` is expected to be
+  pub fn path(&self, day: u32) -> PathBuf {
+    self.dir.join(format!("day{:02}.txt", day))
+  }
+
+  /// Use `contents` as the input for `day` instead of reading it from the directory
+  pub fn insert(&mut self, day: u32, contents: String) {
+    self.cache.insert(day, contents);
+  }
+
+  /// The input for `day`, read from the directory the first time it's asked for
+  pub fn get(&mut self, day: u32) -> Result<&str> {
+    if !self.cache.contains_key(&day) {
+      let path = self.path(day);
+      if !path.is_file() {
+        return Err(Error::MissingInput { day, path });
+      }
+      let contents = read_file(&path)?;
+      self.cache.insert(day, contents);
+    }
+    Ok(&self.cache[&day])
+  }
+}
+
+/// Read a whole input file
+pub fn read_file(path: &Path) -> Result<String> {
+  fs::read_to_string(path).map_err(|e| Error::Io {
+    path: path.to_path_buf(),
+    message: e.to_string(),
+  })
+}
+
+/// Read a whole input from standard input
+pub fn read_stdin() -> Result<String> {
+  let mut contents = String::new();
+  io::stdin()
+    .read_to_string(&mut contents)
+    .map_err(|e| Error::Io {
+      path: "stdin".into(),
+      message: e.to_string(),
+    })?;
+  Ok(contents)
+}
@@ -1,3 +1,17 @@
-#![feature(min_const_generics, array_map)]
-
+pub mod answers;
+pub mod assignment;
+pub mod automaton;
+pub mod bench;
+pub mod console;
 pub mod day;
+mod error;
+pub mod expression;
+pub mod grammar;
+pub mod grid;
+pub mod hex;
+pub mod input;
+pub mod math;
+mod solution;
+
+pub use error::{Error, Result};
+pub use solution::{DynSolution, Solution, Unsolved};
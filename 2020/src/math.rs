@@ -0,0 +1,200 @@
+use std::fmt::Debug;
+use std::ops::{Add, Div, Mul, Rem, Sub};
+
+#[cfg(feature = "bigint")]
+pub use num_bigint::BigInt;
+
+/// Signed integers the functions here work with. `i64` and `i128` qualify,
+/// as does `BigInt` with the `bigint` feature enabled.
+pub trait Integer:
+  Clone
+  + Ord
+  + Debug
+  + From<i64>
+  + Add<Output = Self>
+  + Sub<Output = Self>
+  + Mul<Output = Self>
+  + Div<Output = Self>
+  + Rem<Output = Self>
+{
+}
+
+impl<T> Integer for T where
+  T: Clone
+    + Ord
+    + Debug
+    + From<i64>
+    + Add<Output = T>
+    + Sub<Output = T>
+    + Mul<Output = T>
+    + Div<Output = T>
+    + Rem<Output = T>
+{
+}
+
+fn zero<T: Integer>() -> T {
+  T::from(0)
+}
+
+fn one<T: Integer>() -> T {
+  T::from(1)
+}
+
+fn abs<T: Integer>(n: T) -> T {
+  if n < zero() {
+    zero::<T>() - n
+  } else {
+    n
+  }
+}
+
+/// `a` modulo `m`, always between 0 and `|m|`
+///
+/// # Example
+///
+/// ```
+/// use advent_of_code_2020::math::mod_floor;
+///
+/// assert_eq!(mod_floor(-7i64, 3), 2);
+/// assert_eq!(mod_floor(7i128, 3), 1);
+/// ```
+pub fn mod_floor<T: Integer>(a: T, m: T) -> T {
+  let m = abs(m);
+  ((a % m.clone()) + m.clone()) % m
+}
+
+/// Greatest common divisor, never negative
+pub fn gcd<T: Integer>(a: T, b: T) -> T {
+  abs(egcd(a, b).0)
+}
+
+/// Least common multiple, never negative
+///
+/// # Example
+///
+/// ```
+/// use advent_of_code_2020::math::lcm;
+///
+/// assert_eq!(lcm(4i64, 6), 12);
+/// assert_eq!(lcm(-4i64, 6), 12);
+/// assert_eq!(lcm(0i64, 6), 0);
+/// ```
+pub fn lcm<T: Integer>(a: T, b: T) -> T {
+  if a == zero() || b == zero() {
+    return zero();
+  }
+  abs(a.clone() / gcd(a, b.clone()) * b)
+}
+
+/// Extended Euclidean algorithm, returning `(g, x, y)` where `g` is the
+/// greatest common divisor of `a` and `b` and `a * x + b * y == g`.
+///
+/// # Example
+///
+/// ```
+/// use advent_of_code_2020::math::egcd;
+///
+/// let (g, x, y) = egcd(240i64, 46);
+/// assert_eq!(g, 2);
+/// assert_eq!(240 * x + 46 * y, 2);
+/// ```
+pub fn egcd<T: Integer>(a: T, b: T) -> (T, T, T) {
+  let (mut old_r, mut r) = (a, b);
+  let (mut old_x, mut x) = (one::<T>(), zero::<T>());
+  let (mut old_y, mut y) = (zero::<T>(), one::<T>());
+  while r != zero() {
+    let q = old_r.clone() / r.clone();
+    let next_r = old_r - q.clone() * r.clone();
+    old_r = std::mem::replace(&mut r, next_r);
+    let next_x = old_x - q.clone() * x.clone();
+    old_x = std::mem::replace(&mut x, next_x);
+    let next_y = old_y - q * y.clone();
+    old_y = std::mem::replace(&mut y, next_y);
+  }
+  (old_r, old_x, old_y)
+}
+
+/// The `x` between 0 and `m` where `a * x` is 1 modulo `m`, if there is one
+///
+/// # Example
+///
+/// ```
+/// use advent_of_code_2020::math::mod_inv;
+///
+/// assert_eq!(mod_inv(3i64, 11), Some(4));
+/// assert_eq!(mod_inv(2i64, 4), None);
+/// ```
+pub fn mod_inv<T: Integer>(a: T, m: T) -> Option<T> {
+  let (g, x, _) = egcd(mod_floor(a, m.clone()), abs(m.clone()));
+  if g == one() {
+    Some(mod_floor(x, m))
+  } else {
+    None
+  }
+}
+
+/// `base` to the power of `exp` modulo `m`, for a non-negative `exp`
+///
+/// # Example
+///
+/// ```
+/// use advent_of_code_2020::math::mod_pow;
+///
+/// assert_eq!(mod_pow(4i64, 13, 497), 445);
+/// assert_eq!(mod_pow(7i128, 0, 13), 1);
+/// ```
+pub fn mod_pow<T: Integer>(base: T, exp: T, m: T) -> T {
+  let two = T::from(2);
+  let mut result = mod_floor(one(), m.clone());
+  let mut base = mod_floor(base, m.clone());
+  let mut exp = exp;
+  while exp > zero() {
+    if exp.clone() % two.clone() == one() {
+      result = result * base.clone() % m.clone();
+    }
+    base = base.clone() * base % m.clone();
+    exp = exp / two.clone();
+  }
+  result
+}
+
+/// Solve the system of congruences `x ≡ residue (mod modulus)` for each
+/// `(residue, modulus)`, returning `(x, m)` where every solution is `x`
+/// plus a multiple of `m`, and `0 <= x < m`. The moduli don't need to be
+/// coprime, and `None` means the congruences contradict each other.
+///
+/// Intermediate values reach twice `m` and the square of the largest
+/// modulus, so use `i128` (or `BigInt`) when that could overflow `i64`.
+///
+/// # Example
+///
+/// ```
+/// use advent_of_code_2020::math::crt;
+///
+/// assert_eq!(crt(&[(2i64, 3), (3, 5), (2, 7)]), Some((23, 105)));
+/// // 6 and 4 share a factor of 2
+/// assert_eq!(crt(&[(3i64, 6), (1, 4)]), Some((9, 12)));
+/// assert_eq!(crt(&[(0i64, 6), (1, 4)]), None);
+/// ```
+pub fn crt<T: Integer>(congruences: &[(T, T)]) -> Option<(T, T)> {
+  congruences
+    .iter()
+    .cloned()
+    .try_fold((zero::<T>(), one::<T>()), |(a1, m1), (a2, m2)| {
+      let m2 = abs(m2);
+      let (g, p, _) = egcd(m1.clone(), m2.clone());
+      let diff = a2 - a1.clone();
+      if diff.clone() % g.clone() != zero() {
+        return None;
+      }
+      // a1 + m1 * k satisfies the second congruence when
+      // m1 / g * k ≡ diff / g (mod m2 / g), and p inverts m1 / g there
+      let reduced = m2 / g.clone();
+      let k = mod_floor(
+        mod_floor(diff / g, reduced.clone()) * mod_floor(p, reduced.clone()),
+        reduced.clone(),
+      );
+      let m = m1.clone() * reduced;
+      Some((mod_floor(a1 + m1 * k, m.clone()), m))
+    })
+}
@@ -0,0 +1,61 @@
+use std::any::Any;
+use std::fmt::{self, Display};
+
+use crate::Result;
+
+/// A day's puzzle, split into parsing the raw input and solving both parts
+/// against the parsed result.
+pub trait Solution {
+  type Input;
+  type Part1: Display;
+  type Part2: Display;
+
+  fn parse(input: &str) -> Result<Self::Input>;
+  fn part1(input: &Self::Input) -> Self::Part1;
+  fn part2(input: &Self::Input) -> Self::Part2;
+}
+
+/// Answer for a part that has no solution yet.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Unsolved;
+
+impl Display for Unsolved {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "not solved")
+  }
+}
+
+/// Object safe view of a `Solution` so tooling can hold every day in one table.
+/// The parsed input is passed around as `Any` and answers are formatted to strings.
+pub trait DynSolution {
+  fn parse_dyn(&self, input: &str) -> Result<Box<dyn Any>>;
+  fn part1_dyn(&self, input: &dyn Any) -> String;
+  fn part2_dyn(&self, input: &dyn Any) -> String;
+}
+
+impl<S> DynSolution for S
+where
+  S: Solution,
+  S::Input: 'static,
+{
+  fn parse_dyn(&self, input: &str) -> Result<Box<dyn Any>> {
+    S::parse(input).map(|parsed| Box::new(parsed) as Box<dyn Any>)
+  }
+
+  fn part1_dyn(&self, input: &dyn Any) -> String {
+    S::part1(downcast::<S>(input)).to_string()
+  }
+
+  fn part2_dyn(&self, input: &dyn Any) -> String {
+    S::part2(downcast::<S>(input)).to_string()
+  }
+}
+
+fn downcast<S: Solution>(input: &dyn Any) -> &S::Input
+where
+  S::Input: 'static,
+{
+  input
+    .downcast_ref()
+    .expect("input was parsed by a different solution")
+}
@@ -0,0 +1,99 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use serde_json::Value;
+
+fn aoc(args: &[&str]) -> String {
+  let output = Command::new(env!("CARGO_BIN_EXE_aoc"))
+    .args(args)
+    .output()
+    .expect("the aoc binary runs");
+  assert!(
+    output.status.success(),
+    "aoc {:?} failed: {}",
+    args,
+    String::from_utf8_lossy(&output.stderr)
+  );
+  String::from_utf8(output.stdout).expect("aoc prints UTF-8")
+}
+
+#[test]
+fn bench_json_is_the_only_output() {
+  let stdout = aoc(&[
+    "bench",
+    "--day",
+    "12",
+    "--json",
+    "--warmup",
+    "1",
+    "--iterations",
+    "2",
+  ]);
+  let report: Value = serde_json::from_str(&stdout).expect("bench --json prints only JSON");
+  assert_eq!(report["warmup"], 1);
+  assert_eq!(report["iterations"], 2);
+  let days = report["days"].as_array().unwrap();
+  assert_eq!(days.len(), 1);
+  assert_eq!(days[0]["day"], 12);
+  for stage in &["parse", "part1", "part2"] {
+    let timing = &days[0][*stage];
+    assert!(timing["min_ns"].as_u64().unwrap() <= timing["max_ns"].as_u64().unwrap());
+  }
+}
+
+/// Run one day on `input` given on stdin, returning what it printed to stderr
+fn aoc_failing(day: &str, input: &str) -> String {
+  let mut child = Command::new(env!("CARGO_BIN_EXE_aoc"))
+    .args(["--day", day, "--input", "-"])
+    .stdin(Stdio::piped())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .spawn()
+    .expect("the aoc binary runs");
+  child
+    .stdin
+    .take()
+    .unwrap()
+    .write_all(input.as_bytes())
+    .unwrap();
+  let output = child.wait_with_output().unwrap();
+  assert_eq!(output.status.code(), Some(1), "day {} on {:?}", day, input);
+  String::from_utf8(output.stderr).expect("aoc prints UTF-8")
+}
+
+#[test]
+fn inputs_the_parts_cant_solve_are_parse_errors() {
+  let cases = [
+    ("7", "", "Day 07: expected a rule for shiny gold bags at line 1, column 1: \"\""),
+    (
+      "7",
+      "faded blue bags contain no other bags.\n",
+      "Day 07: expected a rule for shiny gold bags at line 2, column 1: \"\"",
+    ),
+    ("8", "", "Day 08: expected at least one instruction at line 1, column 1: \"\""),
+    (
+      "8",
+      "nop +0\nacc +1\n",
+      "Day 08: program already completes without a swap at line 1, column 1: \"nop +0\"",
+    ),
+    (
+      "8",
+      "jmp +0\njmp +0\n",
+      "Day 08: no single swap lets the program complete at line 1, column 1: \"jmp +0\"",
+    ),
+    (
+      "9",
+      "1\n2\n3\n",
+      "Day 09: expected more than the 25 number preamble at line 4, column 1: \"\"",
+    ),
+    ("10", "\n", "Day 10: expected at least one adapter at line 2, column 1: \"\""),
+    (
+      "22",
+      "Player 1:\n\nPlayer 2:\n",
+      "Day 22: expected at least one card to play at line 1, column 1: \"Player 1:\\n\\nPlayer 2:\"",
+    ),
+  ];
+  for (day, input, message) in cases.iter() {
+    assert_eq!(aoc_failing(day, input).trim_end(), *message);
+  }
+}
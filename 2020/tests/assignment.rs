@@ -0,0 +1,92 @@
+use advent_of_code_2020::assignment::{assign, AssignmentError};
+use proptest::prelude::*;
+
+/// Every way of giving each item a different one of its candidates
+fn all_assignments(candidates: &[Vec<usize>]) -> Vec<Vec<usize>> {
+  fn extend(candidates: &[Vec<usize>], chosen: &mut Vec<usize>, all: &mut Vec<Vec<usize>>) {
+    match candidates.get(chosen.len()) {
+      None => all.push(chosen.clone()),
+      Some(options) => {
+        for &choice in options {
+          if !chosen.contains(&choice) {
+            chosen.push(choice);
+            extend(candidates, chosen, all);
+            chosen.pop();
+          }
+        }
+      }
+    }
+  }
+  let mut all = Vec::new();
+  extend(candidates, &mut Vec::new(), &mut all);
+  all
+}
+
+/// The most items that can be given a different candidate each
+fn most_matched(candidates: &[Vec<usize>]) -> usize {
+  (0..=candidates.len())
+    .rev()
+    .find(|&size| {
+      (0u32..1 << candidates.len())
+        .filter(|subset| subset.count_ones() as usize == size)
+        .any(|subset| {
+          let chosen: Vec<Vec<usize>> = (0..candidates.len())
+            .filter(|item| subset & 1 << item != 0)
+            .map(|item| candidates[item].clone())
+            .collect();
+          !all_assignments(&chosen).is_empty()
+        })
+    })
+    .unwrap()
+}
+
+fn is_valid(candidates: &[Vec<usize>], assignment: &[usize]) -> bool {
+  assignment.len() == candidates.len()
+    && assignment
+      .iter()
+      .zip(candidates)
+      .all(|(choice, options)| options.contains(choice))
+    && (0..assignment.len()).all(|i| !assignment[i + 1..].contains(&assignment[i]))
+}
+
+fn candidates() -> impl Strategy<Value = Vec<Vec<usize>>> {
+  (1usize..7, 1usize..8).prop_flat_map(|(items, choices)| {
+    prop::collection::vec(
+      prop::collection::btree_set(0..choices, 0..=choices.min(4))
+        .prop_map(|set| set.into_iter().collect()),
+      items,
+    )
+  })
+}
+
+proptest! {
+  #[test]
+  fn agrees_with_brute_force(candidates in candidates()) {
+    let all = all_assignments(&candidates);
+    match assign(&candidates) {
+      Ok(assignment) => prop_assert_eq!(all, vec![assignment]),
+      Err(AssignmentError::NoAssignment(unmatched)) => {
+        prop_assert!(all.is_empty());
+        prop_assert_eq!(unmatched.len(), candidates.len() - most_matched(&candidates));
+      }
+      Err(AssignmentError::Ambiguous(first, second)) => {
+        prop_assert!(all.len() > 1);
+        prop_assert!(is_valid(&candidates, &first));
+        prop_assert!(is_valid(&candidates, &second));
+        prop_assert_ne!(first, second);
+      }
+    }
+  }
+}
+
+#[test]
+fn long_chains_resolve() {
+  // Item `n` could be any of choices `0..=n`, which only works one way
+  let candidates: Vec<Vec<usize>> = (0..200).map(|n| (0..=n).rev().collect()).collect();
+  assert_eq!(assign(&candidates), Ok((0..200).collect()));
+}
+
+#[test]
+fn no_items() {
+  assert_eq!(assign(&[]), Ok(vec![]));
+}
@@ -0,0 +1,176 @@
+use std::fmt;
+
+use advent_of_code_2020::console::{
+  assemble, disassemble, repair, Command, Exit, Instruction, InstructionSet, Operand, Registers, Vm,
+};
+
+const MILLION: usize = 1_000_000;
+
+/// An instruction set beyond the puzzle's, with more registers and a
+/// conditional jump, running on the same VM
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Op {
+  Set(usize, i32),
+  Add(usize, usize),
+  Dec(usize),
+  Jnz(usize, i32),
+}
+
+impl Op {
+  fn instruction_set() -> InstructionSet<Op> {
+    use Operand::*;
+    InstructionSet::new()
+      .with("set", &[Register, Number], |args| {
+        Op::Set(args[0] as usize, args[1])
+      })
+      .with("add", &[Register, Register], |args| {
+        Op::Add(args[0] as usize, args[1] as usize)
+      })
+      .with("dec", &[Register], |args| Op::Dec(args[0] as usize))
+      .with("jnz", &[Register, Target], |args| {
+        Op::Jnz(args[0] as usize, args[1])
+      })
+  }
+}
+
+impl Instruction for Op {
+  fn execute(self, registers: &mut Registers) -> isize {
+    match self {
+      Op::Set(r, value) => registers[r] = value,
+      Op::Add(r, s) => registers[r] += registers[s],
+      Op::Dec(r) => registers[r] -= 1,
+      Op::Jnz(r, offset) if registers[r] != 0 => return offset as isize,
+      Op::Jnz(..) => {}
+    }
+    1
+  }
+
+  fn encode(self) -> (&'static str, Vec<i32>) {
+    match self {
+      Op::Set(r, value) => ("set", vec![r as i32, value]),
+      Op::Add(r, s) => ("add", vec![r as i32, s as i32]),
+      Op::Dec(r) => ("dec", vec![r as i32]),
+      Op::Jnz(r, offset) => ("jnz", vec![r as i32, offset]),
+    }
+  }
+}
+
+impl fmt::Display for Op {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let (mnemonic, args) = self.encode();
+    write!(f, "{} {:?}", mnemonic, args)
+  }
+}
+
+const MULTIPLY: &str = "; acc = r1 * r2
+        set r1, +6
+        set r2, +7
+  outer: add acc, r1
+        dec r2
+        jnz r2, outer
+";
+
+#[test]
+fn extended_instructions_run_on_the_vm() {
+  let set = Op::instruction_set();
+  let program = assemble(MULTIPLY, &set).unwrap();
+  assert_eq!(program[4], Op::Jnz(2, -2));
+
+  // The loop revisits instructions, so step instead of `run`
+  let mut vm = Vm::new(&program);
+  while vm.step().is_some() {}
+  assert_eq!(vm.pc(), 5);
+  assert_eq!(vm.registers(), &[42, 6, 0, 0]);
+}
+
+#[test]
+fn extended_instructions_round_trip() {
+  let set = Op::instruction_set();
+  let program = assemble(MULTIPLY, &set).unwrap();
+  let text = disassemble(&program, &set);
+  assert_eq!(
+    text,
+    "       set r1, +6\n       set r2, +7\nL2:    add acc, r1\n       dec r2\n       jnz r2, L2\n"
+  );
+  assert_eq!(assemble(&text, &set).unwrap(), program);
+}
+
+#[test]
+fn assembly_errors_point_at_the_problem() {
+  let set = Op::instruction_set();
+  let err = assemble("set r1, +1\njnz r1, nowhere", &set).unwrap_err();
+  assert_eq!(
+    err.to_string(),
+    "undefined label at line 2, column 9: \"nowhere\""
+  );
+  let err = assemble("set r9, +1", &set).unwrap_err();
+  assert_eq!(
+    err.to_string(),
+    "expected a register name at line 1, column 5: \"r9\""
+  );
+  let err = assemble("mul r1, r2", &set).unwrap_err();
+  assert_eq!(
+    err.to_string(),
+    "expected one of `set`, `add`, `dec`, `jnz` at line 1, column 1: \"mul\""
+  );
+}
+
+#[test]
+fn looping_programs_stop_the_vm() {
+  let program = assemble("set r1, +1\nloop: jnz r1, loop", &Op::instruction_set()).unwrap();
+  assert_eq!(Vm::new(&program).run(), Exit::Looped(1));
+}
+
+#[test]
+fn million_instructions_terminate() {
+  let program = vec![Command::Add(1); MILLION];
+  let mut vm = Vm::new(&program);
+  assert_eq!(vm.run(), Exit::Terminated);
+  assert_eq!(vm.acc(), MILLION as i32);
+  assert_eq!(vm.steps(), MILLION);
+}
+
+#[test]
+fn million_instructions_loop() {
+  // Jump to the last instruction, then walk backwards to the first
+  let mut program = vec![Command::Jump(-1); MILLION];
+  program[0] = Command::Jump(MILLION as i32 - 1);
+  let mut vm = Vm::new(&program);
+  assert_eq!(vm.run(), Exit::Looped(0));
+  assert_eq!(vm.steps(), MILLION);
+}
+
+#[test]
+fn million_instructions_jump_out_of_bounds() {
+  let mut program = vec![Command::Noop(0); MILLION];
+  program[MILLION - 1] = Command::Jump(5);
+  assert_eq!(
+    Vm::new(&program).run(),
+    Exit::OutOfBounds(MILLION as isize + 4)
+  );
+}
+
+#[test]
+fn million_instructions_repair() {
+  let mut program = vec![Command::Add(1); MILLION];
+  program[MILLION - 1] = Command::Jump(1 - MILLION as i32);
+  assert_eq!(repair(&program), Some(MILLION - 1));
+  program[MILLION - 1] = program[MILLION - 1].flipped();
+  let mut vm = Vm::new(&program);
+  assert_eq!(vm.run(), Exit::Terminated);
+  assert_eq!(vm.acc(), MILLION as i32 - 1);
+}
+
+#[test]
+fn step_budget_stops_the_vm() {
+  let program = vec![Command::Add(1); MILLION];
+  let mut vm = Vm::new(&program).with_budget(1000);
+  assert_eq!(vm.run(), Exit::OutOfSteps);
+  assert_eq!((vm.steps(), vm.acc(), vm.pc()), (1000, 1000, 1000));
+  vm.set_budget(Some(MILLION));
+  assert_eq!(vm.run(), Exit::Terminated);
+  vm.reset();
+  vm.set_budget(Some(0));
+  assert_eq!(vm.run(), Exit::OutOfSteps);
+  assert_eq!(vm.steps(), 0);
+}
@@ -0,0 +1,18 @@
+use advent_of_code_2020::day::day05::parse;
+use advent_of_code_2020::Error;
+
+#[test]
+fn passes_are_ten_characters_not_bytes() {
+  assert_eq!(
+    parse("FFFFFFFLé").unwrap_err(),
+    Error::parse(1, 1, "FFFFFFFLé", "expected 10 characters")
+  );
+  assert_eq!(
+    parse("BFFFBBFRRR\nFFFFFFFLéR").unwrap_err(),
+    Error::parse(2, 9, "é", "expected F/B then L/R")
+  );
+  assert_eq!(
+    parse("éFFFFFFLRL").unwrap_err(),
+    Error::parse(1, 1, "é", "expected F/B then L/R")
+  );
+}
@@ -0,0 +1,59 @@
+use advent_of_code_2020::day::day07::parse;
+
+#[test]
+fn cyclic_rules_are_rejected() {
+  let err = parse(
+    "light red bags contain 1 dark blue bag.\n\
+     dark blue bags contain 2 pale green bags, 1 faded blue bag.\n\
+     faded blue bags contain no other bags.\n\
+     pale green bags contain 1 light red bag.",
+  )
+  .unwrap_err();
+  assert_eq!(
+    err.to_string(),
+    "bags end up inside themselves: light red -> dark blue -> pale green -> light red \
+     at line 1, column 1: \"light red bags contain 1 dark blue bag.\""
+  );
+
+  let err = parse("shiny gold bags contain 1 shiny gold bag.").unwrap_err();
+  assert!(err
+    .to_string()
+    .starts_with("bags end up inside themselves: shiny gold -> shiny gold"));
+}
+
+#[test]
+fn every_colour_needs_one_rule() {
+  let err = parse("light red bags contain 1 dark blue bag.").unwrap_err();
+  assert_eq!(
+    err.to_string(),
+    "no rule for this colour at line 1, column 26: \"dark blue\""
+  );
+  let err = parse("dark blue bags contain no other bags.\ndark blue bags contain no other bags.")
+    .unwrap_err();
+  assert_eq!(
+    err.to_string().split(" at ").nth(1),
+    Some("line 2, column 1: \"dark blue bags contain no other bags.\"")
+  );
+}
+
+#[test]
+fn deep_nesting_is_counted_without_recursion() {
+  // 5000 colours, each holding 1 of the next
+  let mut rules: Vec<String> = (0..5000)
+    .map(|n| format!("c{} bags contain 1 c{} bag.", n, n + 1))
+    .collect();
+  rules.push("c5000 bags contain no other bags.".into());
+  let bags = parse(&rules.join("\n")).unwrap();
+  assert_eq!(bags.count_inside(bags.id("c0").unwrap()), 5000);
+  assert_eq!(bags.containers(bags.id("c5000").unwrap()).len(), 5000);
+}
+
+#[test]
+fn counts_that_overflow_are_an_error() {
+  let mut rules: Vec<String> = (0..30)
+    .map(|n| format!("c{} bags contain 1000 c{} bags.", n, n + 1))
+    .collect();
+  rules.push("c30 bags contain no other bags.".into());
+  let err = parse(&rules.join("\n")).unwrap_err();
+  assert!(err.to_string().starts_with("too many bags inside to count"));
+}
@@ -0,0 +1,47 @@
+use advent_of_code_2020::day::day13::{parse, part2};
+use advent_of_code_2020::Error;
+
+#[test]
+fn bus_ids_start_at_one() {
+  assert_eq!(
+    parse("939\n7,x,0,19").unwrap_err(),
+    Error::parse(2, 5, "0", "bus ids must be at least 1")
+  );
+  assert_eq!(
+    parse("939\n-7").unwrap_err(),
+    Error::parse(2, 1, "-7", "bus ids must be at least 1")
+  );
+}
+
+#[test]
+fn some_bus_must_be_in_service() {
+  assert_eq!(
+    parse("939\nx,x,x").unwrap_err(),
+    Error::parse(2, 1, "x,x,x", "expected at least one bus in service")
+  );
+}
+
+#[test]
+fn buses_must_be_able_to_line_up() {
+  assert_eq!(
+    parse("939\n2,4").unwrap_err(),
+    Error::parse(2, 3, "4", "bus can't line up with the ones before it")
+  );
+  // Sharing a factor is fine as long as the offsets agree
+  assert_eq!(part2(&parse("939\n4,x,6").unwrap()), 4);
+}
+
+#[test]
+fn schedules_that_overflow_128_bits_are_rejected() {
+  let input = "939\n1000000000000000000,1000000000000000001,x,1000000000000000003";
+  assert_eq!(
+    parse(input).unwrap_err(),
+    Error::parse(
+      2,
+      43,
+      "1000000000000000003",
+      "schedule doesn't repeat within 128 bits"
+    )
+  );
+  assert!(parse("939\n1000000000000000000,1000000000000000001").is_ok());
+}
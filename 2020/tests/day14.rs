@@ -0,0 +1,42 @@
+use advent_of_code_2020::day::day14::parse;
+use advent_of_code_2020::Error;
+
+const MASK: &str = "mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X";
+
+#[test]
+fn memory_is_written_after_a_mask() {
+  assert_eq!(
+    parse("mem[8] = 11").unwrap_err(),
+    Error::parse(
+      1,
+      1,
+      "mem[8] = 11",
+      "expected a mask before writing to memory"
+    )
+  );
+}
+
+#[test]
+fn values_fit_in_36_bits() {
+  let input = format!("{}\nmem[8] = 11\nmem[7] = -1", MASK);
+  assert_eq!(
+    parse(&input).unwrap_err(),
+    Error::parse(3, 10, "-1", "values must fit in 36 bits")
+  );
+  let input = format!("{}\nmem[8] = 68719476736", MASK);
+  assert_eq!(
+    parse(&input).unwrap_err(),
+    Error::parse(2, 10, "68719476736", "values must fit in 36 bits")
+  );
+  let input = format!("{}\nmem[8] = 68719476735", MASK);
+  assert!(parse(&input).is_ok());
+}
+
+#[test]
+fn addresses_fit_in_36_bits() {
+  let input = format!("{}\nmem[68719476736] = 1", MASK);
+  assert_eq!(
+    parse(&input).unwrap_err(),
+    Error::parse(2, 1, "mem[68719476736]", "addresses must fit in 36 bits")
+  );
+}
@@ -0,0 +1,59 @@
+use advent_of_code_2020::day::day16::{decode, parse, report};
+
+#[test]
+fn csv_quotes_awkward_field_names() {
+  let notes = parse(
+    "size, in cm: 1-5 or 7-9\nsay \"hi\": 10-20 or 30-40\n\nyour ticket:\n3,15\n\nnearby tickets:\n35,8\n4,50",
+  )
+  .unwrap();
+  let report = report(&notes).unwrap();
+  assert_eq!(report.columns, vec!["say \"hi\"", "size, in cm"]);
+  assert_eq!(
+    report.to_csv(),
+    "ticket,\"size, in cm\",\"say \"\"hi\"\"\"\n0,8,35\n"
+  );
+  assert_eq!(report.invalid[0].index, 1);
+}
+
+#[test]
+fn tickets_need_a_value_for_each_rule() {
+  let err = parse("a: 1-2 or 4-5\nb: 1-2 or 4-5\n\nyour ticket:\n1,2\n\nnearby tickets:\n1,2,3")
+    .unwrap_err();
+  assert_eq!(
+    err.to_string(),
+    "expected 2 values at line 8, column 1: \"1,2,3\""
+  );
+}
+
+#[test]
+fn fields_that_cant_be_told_apart_are_reported() {
+  let notes =
+    parse("a: 1-2 or 4-5\nb: 1-2 or 4-5\n\nyour ticket:\n1,2\n\nnearby tickets:\n1,4").unwrap();
+  assert!(report(&notes).is_err());
+}
+
+#[test]
+fn field_order_errors_point_at_the_rule() {
+  let err = decode("a: 1-2 or 4-5\nb: 10-11 or 20-21\n\nyour ticket:\n1,2\n\nnearby tickets:\n1,4")
+    .unwrap_err();
+  assert_eq!(
+    err.to_string(),
+    "no column is left for this field at line 2, column 1: \"b: 10-11 or 20-21\""
+  );
+  let err = decode("a: 1-2 or 4-5\nb: 1-2 or 4-5\n\nyour ticket:\n1,2\n\nnearby tickets:\n1,4")
+    .unwrap_err();
+  assert_eq!(
+    err.to_string(),
+    "field could be ticket column 1 or 2 at line 1, column 1: \"a: 1-2 or 4-5\""
+  );
+}
+
+#[test]
+fn ranges_can_end_at_the_largest_value() {
+  let notes = parse(
+    "a: 0-1 or 5-9223372036854775807\n\nyour ticket:\n9223372036854775807\n\nnearby tickets:\n2",
+  )
+  .unwrap();
+  assert!(notes.rules()[0].allows(i64::MAX));
+  assert!(!notes.rules()[0].allows(2));
+}
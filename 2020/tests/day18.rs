@@ -0,0 +1,36 @@
+use advent_of_code_2020::day::day18::{parse, part1, part2};
+use advent_of_code_2020::Error;
+
+#[test]
+fn division_by_zero_is_a_parse_error() {
+  assert_eq!(
+    parse("1 + 2\n1 / 0").unwrap_err(),
+    Error::parse(2, 1, "1 / 0", "expression overflows or divides by zero")
+  );
+}
+
+#[test]
+fn overflow_is_a_parse_error() {
+  let line = "3000000000 * 3000000000 * 3000000000";
+  assert_eq!(
+    parse(line).unwrap_err(),
+    Error::parse(1, 1, line, "expression overflows or divides by zero")
+  );
+  // Only overflows when the addition is done first
+  let line = "4611686018427387904 * 1 + 1";
+  assert_eq!(
+    parse(line).unwrap_err(),
+    Error::parse(1, 1, line, "expression overflows or divides by zero")
+  );
+  assert_eq!(
+    parse("9223372036854775807\n1").unwrap_err(),
+    Error::parse(2, 1, "1", "sum of the expressions overflows")
+  );
+}
+
+#[test]
+fn each_part_uses_its_own_precedence() {
+  let homework = parse("2 * 3 + 4\n\n10 - 4 / 2").unwrap();
+  assert_eq!(part1(&homework), 10 + 3);
+  assert_eq!(part2(&homework), 14 + 3);
+}
@@ -0,0 +1,247 @@
+use advent_of_code_2020::day::day20::{
+  assemble, find_pattern, parse, part1, roughness, AssemblyError, Pattern, SEA_MONSTER,
+};
+use advent_of_code_2020::grid::Grid;
+use std::collections::HashMap;
+
+/// A small xorshift generator so the puzzles are random but repeatable
+struct Rng(u64);
+
+impl Rng {
+  fn next(&mut self) -> u64 {
+    self.0 ^= self.0 << 13;
+    self.0 ^= self.0 >> 7;
+    self.0 ^= self.0 << 17;
+    self.0
+  }
+
+  fn below(&mut self, n: usize) -> usize {
+    (self.next() % n as u64) as usize
+  }
+}
+
+/// A puzzle of `wide` by `high` tiles of `size` pixels, each shuffled and
+/// turned at random, and the image that assembling it should produce
+fn puzzle(seed: u64, size: usize, wide: usize, high: usize) -> (String, Grid<bool>) {
+  let mut rng = Rng(seed);
+  let picture = picture(&mut rng, size, wide, high);
+  cut(&mut rng, &picture, size, wide, high)
+}
+
+/// A random picture for `wide` by `high` tiles where every border only
+/// matches the tile on its other side, like in the real puzzle.
+/// Neighbouring tiles share the line of pixels between them.
+fn picture(rng: &mut Rng, size: usize, wide: usize, high: usize) -> Grid<bool> {
+  let step = size - 1;
+  loop {
+    let picture = Grid::from_fn(wide * step + 1, high * step + 1, |_| rng.next() & 1 == 0);
+    if unambiguous(&picture, size, wide, high) {
+      break picture;
+    }
+  }
+}
+
+/// Cut the picture into tiles, then turn and shuffle them
+fn cut(
+  rng: &mut Rng,
+  picture: &Grid<bool>,
+  size: usize,
+  wide: usize,
+  high: usize,
+) -> (String, Grid<bool>) {
+  let step = size - 1;
+  let mut tiles: Vec<(usize, Grid<bool>)> = Vec::new();
+  for ty in 0..high {
+    for tx in 0..wide {
+      let tile = picture.sub_grid((tx * step, ty * step), size, size);
+      let orientation = rng.below(8);
+      let id = 1000 + ty * wide + tx;
+      tiles.push((id, tile.orientations().swap_remove(orientation)));
+    }
+  }
+  for i in (1..tiles.len()).rev() {
+    tiles.swap(i, rng.below(i + 1));
+  }
+  let text = tiles
+    .iter()
+    .map(|(id, tile)| {
+      format!(
+        "Tile {}:\n{}",
+        id,
+        tile.render(|&b| if b { '#' } else { '.' })
+      )
+    })
+    .collect::<Vec<_>>()
+    .join("\n");
+
+  let inner = size - 2;
+  let image = Grid::from_fn(wide * inner, high * inner, |(x, y)| {
+    picture[(
+      x / inner * step + x % inner + 1,
+      y / inner * step + y % inner + 1,
+    )]
+  });
+  (text, image)
+}
+
+/// Whether every border only matches the tile on its other side
+fn unambiguous(picture: &Grid<bool>, size: usize, wide: usize, high: usize) -> bool {
+  let step = size - 1;
+  let mut borders = HashMap::new();
+  let mut add = |border: Vec<bool>| {
+    let reversed: Vec<bool> = border.iter().rev().copied().collect();
+    let palindrome = border == reversed;
+    *borders.entry(border.min(reversed)).or_insert(0) += 1;
+    !palindrome
+  };
+  for y in 0..=high {
+    for tx in 0..wide {
+      if !add(
+        (0..size)
+          .map(|x| picture[(tx * step + x, y * step)])
+          .collect(),
+      ) {
+        return false;
+      }
+    }
+  }
+  for x in 0..=wide {
+    for ty in 0..high {
+      if !add(
+        (0..size)
+          .map(|y| picture[(x * step, ty * step + y)])
+          .collect(),
+      ) {
+        return false;
+      }
+    }
+  }
+  borders.values().all(|&count| count == 1)
+}
+
+#[test]
+fn synthetic_puzzles_assemble() {
+  for (seed, size, wide, high) in [
+    (1, 10, 3, 3),
+    (2, 14, 5, 5),
+    (3, 12, 4, 2),
+    (4, 16, 2, 6),
+    (5, 20, 1, 4),
+    (6, 16, 7, 7),
+    (7, 6, 2, 2),
+  ] {
+    let (text, expected) = puzzle(seed, size, wide, high);
+    let tiles = parse(&text).unwrap();
+    let stitched = assemble(&tiles).unwrap().image();
+    assert!(
+      expected.orientations().contains(&stitched),
+      "{}x{} tiles of {} pixels didn't assemble",
+      wide,
+      high,
+      size
+    );
+  }
+}
+
+#[test]
+fn synthetic_corners() {
+  let (text, _) = puzzle(7, 10, 4, 3);
+  let corners = [1000i64, 1003, 1008, 1011];
+  let assembly = assemble(&parse(&text).unwrap()).unwrap();
+  assert_eq!(part1(&assembly), corners.iter().product());
+}
+
+#[test]
+fn borders_may_match_more_than_one_tile() {
+  for seed in 1..=5 {
+    let (size, wide, high) = (8, 3, 3);
+    let step = size - 1;
+    let mut rng = Rng(seed);
+    let mut picture = picture(&mut rng, size, wide, high);
+    // The outer bottom border of the bottom right tile and the top border of
+    // the middle tile both match the border between the first two tiles
+    for i in 0..size {
+      let shared = picture[(step, i)];
+      picture[(2 * step + i, 3 * step)] = shared;
+      picture[(step + i, step)] = shared;
+    }
+    let (text, expected) = cut(&mut rng, &picture, size, wide, high);
+    let stitched = assemble(&parse(&text).unwrap()).unwrap().image();
+    assert!(expected.orientations().contains(&stitched), "seed {}", seed);
+  }
+}
+
+#[test]
+fn assembly_must_be_unique() {
+  let solid = "####\n####\n####\n####";
+  let text = format!("Tile 1:\n{}\n\nTile 2:\n####\n#..#\n##.#\n####", solid);
+  match assemble(&parse(&text).unwrap()) {
+    Err(AssemblyError::Ambiguous(first, second)) => assert_ne!(first.layout(), second.layout()),
+    other => panic!("expected two assemblies, got {:?}", other),
+  }
+
+  let text = format!("Tile 1:\n{}\n\nTile 2:\n....\n....\n....\n....", solid);
+  assert_eq!(
+    assemble(&parse(&text).unwrap()),
+    Err(AssemblyError::NoAssembly)
+  );
+}
+
+#[test]
+fn tiles_must_be_square_and_the_same_size() {
+  let err = parse("Tile 1:\n###\n#.#\n###\n\nTile 2:\n####\n#..#\n#..#\n####").unwrap_err();
+  assert_eq!(
+    err.to_string(),
+    "expected 3x3 pixels at line 6, column 1: \"Tile 2:\""
+  );
+  let err = parse("Tile 1:\n###\n#.#").unwrap_err();
+  assert_eq!(
+    err.to_string(),
+    "expected 3x3 pixels at line 1, column 1: \"Tile 1:\""
+  );
+  let row = "#".repeat(65);
+  let big = format!("Tile 1:\n{}", vec![row; 65].join("\n"));
+  assert_eq!(
+    parse(&big).unwrap_err().to_string(),
+    "expected at most 64x64 pixels at line 1, column 1: \"Tile 1:\""
+  );
+}
+
+#[test]
+fn tile_ids_are_unique() {
+  let err = parse("Tile 7:\n###\n#.#\n###\n\nTile 7:\n...\n.#.\n...").unwrap_err();
+  assert_eq!(
+    err.to_string(),
+    "tile id is already used at line 6, column 1: \"Tile 7:\""
+  );
+}
+
+#[test]
+fn overlapping_monsters_count_once() {
+  let monster = Pattern::parse(SEA_MONSTER).unwrap();
+  let mut image = Grid::from_fn(26, 4, |_| false);
+  // Two monsters five pixels apart share three pixels of their middle row
+  for x in [0, 5] {
+    for (dx, dy) in monster.cells() {
+      image[(x + dx, dy + 1)] = true;
+    }
+  }
+  image[(25, 0)] = true;
+  let matches = find_pattern(&image, &monster);
+  let positions: Vec<_> = matches.iter().map(|m| m.position).collect();
+  assert_eq!(positions, vec![(0, 1), (5, 1)]);
+  assert_eq!(image.iter().filter(|&&b| b).count(), 28);
+  assert_eq!(roughness(&image, &matches), 1);
+}
+
+#[test]
+fn patterns_are_checked() {
+  let err = Pattern::parse("#.\n#x").unwrap_err();
+  assert_eq!(
+    err.to_string(),
+    "expected `#`, `.` or a space at line 2, column 2: \"x\""
+  );
+  assert!(Pattern::parse(". .\n...").is_err());
+  let pattern = Pattern::parse(" #\n##\n#").unwrap();
+  assert_eq!((pattern.width(), pattern.height()), (2, 3));
+}
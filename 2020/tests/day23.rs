@@ -0,0 +1,31 @@
+use advent_of_code_2020::day::day23::parse;
+use advent_of_code_2020::Error;
+
+#[test]
+fn labels_are_distinct() {
+  assert_eq!(
+    parse("389125437").unwrap_err(),
+    Error::parse(1, 8, "3", "cup labels must be distinct")
+  );
+}
+
+#[test]
+fn labels_count_up_from_one() {
+  assert_eq!(
+    parse("38912546\n").unwrap_err(),
+    Error::parse(1, 1, "38912546", "cup labels must be 1 to 8, missing 7")
+  );
+  assert_eq!(parse("21534").unwrap(), vec![2, 1, 5, 3, 4]);
+}
+
+#[test]
+fn a_move_needs_five_cups() {
+  assert_eq!(
+    parse("1").unwrap_err(),
+    Error::parse(1, 1, "1", "expected at least 5 cups")
+  );
+  assert_eq!(
+    parse("\n").unwrap_err(),
+    Error::parse(1, 1, "", "expected at least 5 cups")
+  );
+}
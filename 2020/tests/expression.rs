@@ -0,0 +1,51 @@
+use advent_of_code_2020::expression::{parse, Assoc, Op, Precedence, MAX_NESTING};
+use advent_of_code_2020::Error;
+
+#[test]
+fn left_associative_operators_at_the_highest_level() {
+  let table = Precedence::new()
+    .with(Op::Sub, u32::MAX, Assoc::Left)
+    .with(Op::Mul, 1, Assoc::Left);
+  assert_eq!(parse("10 - 2 - 3", &table).unwrap().eval(), Some(5));
+  assert_eq!(
+    parse("2 * 10 - 2 - 3", &table).unwrap().to_string(),
+    "(2 * ((10 - 2) - 3))"
+  );
+  let table = Precedence::new().with(Op::Sub, u32::MAX, Assoc::Right);
+  assert_eq!(parse("10 - 2 - 3", &table).unwrap().eval(), Some(11));
+}
+
+#[test]
+fn nesting_is_limited() {
+  let table = Precedence::standard();
+  let nested = |depth: usize| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+  assert_eq!(parse(&nested(MAX_NESTING), &table).unwrap().eval(), Some(1));
+  assert_eq!(
+    parse(&nested(100_000), &table).unwrap_err(),
+    Error::parse(1, MAX_NESTING + 1, "(", "expression is nested too deeply")
+  );
+
+  let negated = format!("{}1", "-".repeat(100_000));
+  assert_eq!(
+    parse(&negated, &table).unwrap_err(),
+    Error::parse(1, MAX_NESTING + 1, "-", "expression is nested too deeply")
+  );
+
+  let right = Precedence::new().with(Op::Sub, 1, Assoc::Right);
+  let chain = vec!["1"; 100_000].join(" - ");
+  assert_eq!(
+    parse(&chain, &right).unwrap_err(),
+    Error::parse(
+      1,
+      4 * MAX_NESTING + 3,
+      "-",
+      "expression is nested too deeply"
+    )
+  );
+  // Left associative chains don't nest while parsing
+  let left = Precedence::new().with(Op::Sub, 1, Assoc::Left);
+  assert_eq!(
+    parse(&vec!["1"; 1000].join(" - "), &left).unwrap().eval(),
+    Some(-998)
+  );
+}
@@ -0,0 +1,90 @@
+use advent_of_code_2020::math::{crt, egcd, gcd, lcm, mod_floor, mod_inv, mod_pow};
+use proptest::prelude::*;
+
+proptest! {
+  #[test]
+  fn egcd_satisfies_bezout(a in -1_000_000i64..1_000_000, b in -1_000_000i64..1_000_000) {
+    let (g, x, y) = egcd(a, b);
+    prop_assert_eq!(a * x + b * y, g);
+    if g != 0 {
+      prop_assert_eq!(a % g, 0);
+      prop_assert_eq!(b % g, 0);
+    }
+  }
+
+  #[test]
+  fn lcm_is_a_multiple_of_both(a in 1i64..100_000, b in 1i64..100_000) {
+    let l = lcm(a, b);
+    prop_assert_eq!(l % a, 0);
+    prop_assert_eq!(l % b, 0);
+    prop_assert_eq!(l * gcd(a, b), a * b);
+  }
+
+  #[test]
+  fn mod_inv_inverts_when_coprime(a in -100_000i64..100_000, m in 2i64..100_000) {
+    match mod_inv(a, m) {
+      Some(inv) => {
+        prop_assert!((0..m).contains(&inv));
+        prop_assert_eq!(mod_floor(a * inv, m), 1);
+      }
+      None => prop_assert_ne!(gcd(a, m), 1),
+    }
+  }
+
+  #[test]
+  fn mod_pow_matches_repeated_multiplication(base in -1000i64..1000, exp in 0i64..64, m in 1i64..10_000) {
+    let expected = (0..exp).fold(mod_floor(1, m), |acc, _| mod_floor(acc * base, m));
+    prop_assert_eq!(mod_pow(base, exp, m), expected);
+  }
+
+  #[test]
+  fn crt_solves_every_congruence(
+    congruences in prop::collection::vec((0i128..1_000_000, 1i128..1_000), 1..6)
+  ) {
+    match crt(&congruences) {
+      Some((x, m)) => {
+        prop_assert!((0..m).contains(&x));
+        for &(residue, modulus) in &congruences {
+          prop_assert_eq!(mod_floor(x, modulus), mod_floor(residue, modulus));
+          prop_assert_eq!(m % modulus, 0);
+        }
+      }
+      // Only moduli sharing a factor can contradict each other
+      None => prop_assert!(congruences
+        .iter()
+        .any(|&(_, a)| congruences.iter().any(|&(_, b)| gcd(a, b) != 1))),
+    }
+  }
+
+  #[test]
+  fn crt_finds_a_chosen_solution(
+    x in 0i128..1_000_000_000,
+    moduli in prop::collection::vec(1i128..10_000, 1..6)
+  ) {
+    let congruences: Vec<_> = moduli.iter().map(|&m| (x % m, m)).collect();
+    let (solution, m) = crt(&congruences).expect("x solves every congruence");
+    prop_assert_eq!(solution, x % m);
+  }
+}
+
+#[cfg(feature = "bigint")]
+#[test]
+fn crt_with_big_integers() {
+  use advent_of_code_2020::math::BigInt;
+
+  // The product of these primes is far past i128
+  let primes: [i64; 5] = [
+    1_000_000_007,
+    998_244_353,
+    1_000_000_009,
+    2_147_483_647,
+    4_294_967_291,
+  ];
+  let x: BigInt = "123456789012345678901234567890123456789".parse().unwrap();
+  let congruences: Vec<(BigInt, BigInt)> = primes
+    .iter()
+    .map(|&p| (x.clone() % BigInt::from(p), BigInt::from(p)))
+    .collect();
+  let (solution, m) = crt(&congruences).unwrap();
+  assert_eq!(solution, x % m);
+}